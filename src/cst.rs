@@ -1,7 +1,9 @@
 use derive_getters::Getters;
+use std::hash::{Hash, Hasher};
 use std::ops;
 
-use crate::lex::Token;
+use crate::lex::{FloatSuffix, IntSuffix, Token};
+use crate::symbol::Symbol;
 use crate::text::{TextSpan, WithSpan};
 
 mod new;
@@ -11,7 +13,7 @@ mod span;
 
 pub use parser::Parser;
 
-use operators::{AssignmentOperator, BinaryOperator, UnaryOperator};
+pub use operators::{AssignmentOperator, BinaryOperator, UnaryOperator};
 
 #[derive(Debug, Clone, Getters)]
 pub struct Package {
@@ -19,7 +21,7 @@ pub struct Package {
     root: Module,
 }
 
-#[derive(Debug, Clone, Getters)]
+#[derive(Debug, Clone, Default, Getters)]
 pub struct Config {}
 
 #[derive(Debug, Clone, Getters)]
@@ -36,12 +38,16 @@ pub enum Item {
     TypeDecls(TypeDecl),
 }
 
+/// The text of the `##` doc comments right before a global, function or type declaration
+pub type Doc = Option<String>;
+
 #[derive(Debug, Clone, Getters)]
 pub struct Global {
     let_token: Token,
     ident: Ident,
-    ty: Type,
+    ty: Option<Type>,
     value: Expr,
+    doc: Doc,
 }
 
 #[derive(Debug, Clone, Getters)]
@@ -57,26 +63,37 @@ pub struct Function {
     args: Vec<(Ident, Type)>,
     ret_type: Type,
     body: Expr,
+    doc: Doc,
 }
 
 #[derive(Debug, Clone, Getters)]
 pub struct TypeDecl {
-    type_token: Token,
+    type_token: Token, // or the struct token in  struct Name { fields }
     ident: Ident,
     ty: Type,
+    doc: Doc,
 }
 
 #[derive(Debug, Clone)]
-pub struct Ident(String, TextSpan);
+pub struct Ident(Symbol, TextSpan);
 
+// Identifiers are equal and hash the same regardless of where they are
 impl PartialEq for Ident {
     fn eq(&self, other: &Self) -> bool {
         self.0 == other.0
     }
 }
 
+impl Eq for Ident {}
+
+impl Hash for Ident {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
 impl ops::Deref for Ident {
-    type Target = String;
+    type Target = Symbol;
     fn deref(&self) -> &Self::Target {
         &self.0
     }
@@ -91,6 +108,14 @@ impl PartialEq for Path {
     }
 }
 
+impl Eq for Path {}
+
+impl Hash for Path {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
 impl ops::Deref for Path {
     type Target = Vec<Ident>;
     fn deref(&self) -> &Self::Target {
@@ -121,10 +146,16 @@ pub enum TypeInner {
     },
     // The following are only allowed in TypeDecl, Struct, or Enum:
     Struct(Vec<(Ident, Type)>),
-    // TODO: Enum(Vec<(Ident, Option<Type>)>),
+    // enum Name { Unit, Tuple(type1, type2), Other: struct { .. } }
+    // The name is optional, but is needed to refer to the enum's variants
+    Enum {
+        ident: Option<Ident>,
+        variants: Vec<(Ident, Option<Type>)>,
+    },
+    Error,
 }
 
-type Expr = WithSpan<ExprInner>;
+pub type Expr = WithSpan<ExprInner>;
 
 #[derive(Debug, Clone)]
 pub enum ExprInner {
@@ -151,9 +182,16 @@ pub enum ExprInner {
         on: Box<Expr>,
         field: Ident,
     },
+    // start:end  or  start:=end, which includes the end. Both are optional in start:end
+    Range {
+        start: Option<Box<Expr>>,
+        end: Option<Box<Expr>>,
+        inclusive: bool,
+    },
     Tuple(Vec<Expr>),
-    SizedArray(Vec<Expr>, Option<usize>), // [val1, val2] or [val; size]
-    DynamicArray(Vec<Expr>),              // [1, 2, 3, ..]
+    SizedArray(Vec<Expr>, Option<Box<Expr>>), // [val1, val2] or [val; size]
+    DynamicArray(Vec<Expr>),                  // [1, 2, 3, ..]
+    List(Vec<Expr>),                          // @[1, 2, 3]
     Struct {
         path: Path,
         values: Vec<(Ident, Expr)>,
@@ -163,24 +201,57 @@ pub enum ExprInner {
         then: Box<Stmt>,
         els: Option<Box<Stmt>>,
     },
+    Match {
+        on: Box<Expr>,
+        arms: Vec<(Pattern, Expr)>,
+    },
     Loop(Box<Expr>),
     Block(Vec<Stmt>),
+    // fn arg = body  or  fn (arg1, arg2: type) = body
     Closure {
-        args: Vec<Ident>,
+        args: Vec<(Ident, Option<Type>)>,
         body: Box<Expr>,
     },
+    // "text %(expr) text"
+    Interpolated(Vec<InterpolatedPart>),
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub enum InterpolatedPart {
+    Literal(String),
+    Expr(Expr),
 }
 
 #[derive(Debug, Clone)]
 pub enum Literal {
     String(String),
-    Integer(usize),
-    Float(f64),
+    Char(char),
+    Integer(u128, Option<IntSuffix>),
+    Float(f64, Option<FloatSuffix>),
     Bool(bool),
     Null,
 }
 
-type Stmt = WithSpan<StmtInner>;
+pub type Pattern = WithSpan<PatternInner>;
+
+#[derive(Debug, Clone)]
+pub enum PatternInner {
+    Wildcard,            // _
+    Binding(Ident),      // name
+    Literal(Literal),    // 1, "a", true, null
+    Negative(Literal),   // -1, -2.5, as literals have no sign
+    Tuple(Vec<Pattern>), // (pattern1, pattern2)
+    // path::to::Variant  or  path::to::Variant pattern
+    Variant {
+        path: Path,
+        payload: Option<Box<Pattern>>,
+    },
+    // :{ field1, field2: pattern }
+    Struct(Vec<(Ident, Option<Pattern>)>),
+}
+
+pub type Stmt = WithSpan<StmtInner>;
 
 #[derive(Debug, Clone)]
 pub enum StmtInner {
@@ -204,7 +275,8 @@ pub enum StmtInner {
         condition: Expr,
         body: Box<Stmt>,
     },
-    Return(Expr),
+    Return(Option<Expr>),
     Yield(Expr),
-    Break(Expr),
+    Break(Option<Expr>),
+    Error,
 }
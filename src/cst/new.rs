@@ -1,8 +1,8 @@
 use super::*;
 
 impl Ident {
-    pub fn new(string: String, span: TextSpan) -> Self {
-        Self(string, span)
+    pub fn new(symbol: Symbol, span: TextSpan) -> Self {
+        Self(symbol, span)
     }
 }
 
@@ -11,3 +11,64 @@ impl Path {
         Self(idents)
     }
 }
+
+impl Package {
+    pub fn new(config: Config, root: Module) -> Self {
+        Self { config, root }
+    }
+}
+
+impl Module {
+    pub fn new(items: Vec<(bool, Item)>, globals: Vec<(bool, Global)>) -> Self {
+        Self { items, globals }
+    }
+}
+
+impl Import {
+    pub fn new(import_token: Token, path: Path) -> Self {
+        Self { import_token, path }
+    }
+}
+
+impl Function {
+    pub fn new(
+        fn_token: Token,
+        ident: Ident,
+        args: Vec<(Ident, Type)>,
+        ret_type: Type,
+        body: Expr,
+        doc: Doc,
+    ) -> Self {
+        Self {
+            fn_token,
+            ident,
+            args,
+            ret_type,
+            body,
+            doc,
+        }
+    }
+}
+
+impl TypeDecl {
+    pub fn new(type_token: Token, ident: Ident, ty: Type, doc: Doc) -> Self {
+        Self {
+            type_token,
+            ident,
+            ty,
+            doc,
+        }
+    }
+}
+
+impl Global {
+    pub fn new(let_token: Token, ident: Ident, ty: Option<Type>, value: Expr, doc: Doc) -> Self {
+        Self {
+            let_token,
+            ident,
+            ty,
+            value,
+            doc,
+        }
+    }
+}
@@ -66,11 +66,16 @@ impl BinaryOperator {
         }
     }
     pub fn left_assoc(&self) -> bool {
-        true
+        !matches!(self, BinaryOperator::Pow)
     }
     pub fn right_assoc(&self) -> bool {
         !self.left_assoc()
     }
+    /// Comparisons are not associative, `a < b < c` is an error rather than `(a < b) < c`
+    pub fn is_comparison(&self) -> bool {
+        use BinaryOperator::*;
+        matches!(self, Eq | Neq | Lt | Le | Gt | Ge)
+    }
 }
 
 impl UnaryOperator {
@@ -86,10 +91,10 @@ impl UnaryOperator {
     }
     pub fn precedence(&self) -> usize {
         use UnaryOperator::*;
+        // Above every binary operator, except that `-2 ** 2` is `-(2 ** 2)` like in maths
         match self {
-            Ref | Deref => 14,
-            Neg | Abs   => 13,
-            Not         => 14,
+            Ref | Deref | Not => 16,
+            Neg | Abs         => 13,
         }
     }
 }
@@ -1,27 +1,41 @@
 use super::*;
-use crate::diagnostics::Diagnostics;
-use crate::lex::{Lexer, TokenKind};
+use crate::diagnostics::{Applicability, Diagnostics, Suggestion, UnexpectedToken};
+use crate::lex::{Lexer, StringPart, TokenKind};
+use crate::symbol::Symbol;
 
 macro_rules! match_token {
     ($self:expr, $kind:ident) => {
         match_token!($self, TokenKind::$kind, TokenKind::$kind)
     };
     ($self:expr, $kind:pat, $instance:expr) => {{
-        let token = $self.lexer.eat();
-        if !matches!(token.kind(), $kind) {
-            $self
-                .diagnostics
-                .unexpected_token(token)
-                .expected_token(&$instance);
+        if !matches!($self.lexer.peek().kind(), $kind) {
+            // Recovery may have stopped at a token that was already reported
+            if $self.last_unexpected != Some($self.lexer.peek().span().start()) {
+                $self.unexpected().expected_token(&$instance);
+            }
             return None;
         }
-        token
+        $self.lexer.eat()
     }};
 }
 
+/// Parses source code into a concrete syntax tree.
+///
+/// The `parse_*` functions returning an `Option` give up on the first unexpected token. The
+/// public entry points instead recover by skipping ahead to a synchronization point and putting
+/// an `Error` node in the tree, so a whole file is always parsed and every mistake is reported.
+/// The `Error` variants of `TypeInner`, `ExprInner` and `StmtInner` are only ever such nodes,
+/// whose errors are already among the diagnostics.
 pub struct Parser<'a> {
     lexer: Lexer<'a>,
     diagnostics: Diagnostics,
+    // The finishers of the lists currently being parsed, innermost last
+    closers: Vec<TokenKind>,
+    // Where the last unexpected token was reported, to not report it twice
+    last_unexpected: Option<usize>,
+    // How many closers there were when the condition being parsed began, as `:{` then begins
+    // the body rather than a struct literal
+    condition: Option<usize>,
 }
 
 impl<'a> Parser<'a> {
@@ -29,34 +43,270 @@ impl<'a> Parser<'a> {
         Self {
             lexer,
             diagnostics: Diagnostics::default(),
+            closers: vec![],
+            last_unexpected: None,
+            condition: None,
         }
     }
 
-    pub fn diagnostics(&self) -> &Diagnostics {
+    /// The diagnostics of both the parser and its lexer, in the order of where they are
+    pub fn diagnostics(&mut self) -> &Diagnostics {
+        self.diagnostics.append(&mut self.lexer.take_diagnostics());
+        self.diagnostics.sort_by_key(|d| d.span().start());
         &self.diagnostics
     }
 
-    pub fn parse_path(&mut self) -> Option<Path> {
-        let mut path = vec![];
+    /// Reports the next token as unexpected without eating it, skipping it is left to recovery
+    fn unexpected(&mut self) -> UnexpectedToken<'_> {
+        let token = self.lexer.peek().clone();
+        let text = self.lexer.source_text(*token.span());
+        self.last_unexpected = Some(token.span().start());
+        self.diagnostics.unexpected_token(token, text)
+    }
+
+    /// Skips tokens until the next one is in `stop`, closes a list being parsed, begins an item
+    /// on a new line or, if `newline` is set, begins a new line at all. Brackets opened while
+    /// skipping are skipped along with everything inside them. Within parentheses or square
+    /// brackets, a closing bracket that matches nothing is left for the list to close, unless
+    /// it's followed by a token in `stop` and so likely just a typo within the list.
+    fn synchronize(&mut self, stop: &[TokenKind], newline: bool) {
+        let mut depth = 0usize;
         loop {
-            let mut ident_token = self.lexer.eat();
-            let ident = match ident_token.take_kind() {
-                TokenKind::Ident(ident) => ident,
-                _ => {
-                    self.diagnostics
-                        .unexpected_token(ident_token)
-                        .expected_token(&TokenKind::Ident(String::new()));
-                    return None;
+            let next = self.lexer.peek();
+            let kind = next.kind();
+            if *kind == TokenKind::EOF || *next.newline_before() && is_item_start(kind) {
+                break;
+            }
+            if depth == 0
+                && (stop.contains(kind)
+                    || self.closers.contains(kind)
+                    || newline && *next.newline_before())
+            {
+                break;
+            }
+            let stray = depth == 0
+                && matches!(
+                    self.closers.last(),
+                    Some(TokenKind::RightParen | TokenKind::RightSquare)
+                )
+                && is_stray_closer(kind, &self.closers);
+            if stray && !stop.contains(self.lexer.peek_nth(1).kind()) {
+                break;
+            }
+            match self.lexer.peek().kind() {
+                TokenKind::LeftParen | TokenKind::LeftSquare | TokenKind::LeftCurly => depth += 1,
+                TokenKind::RightParen | TokenKind::RightSquare | TokenKind::RightCurly => {
+                    depth = depth.saturating_sub(1)
                 }
-            };
-            path.push(Ident::new(ident, *ident_token.span()));
+                _ => {}
+            }
+            self.lexer.eat();
+        }
+    }
 
-            if *ident_token.whitespace_after() {
-                break;
+    /// Runs `parser`, synchronizing if it fails. The error is the span of everything `parser`
+    /// consumed or was skipped afterwards.
+    fn recover<T, P: FnOnce(&mut Self) -> Option<T>>(
+        &mut self,
+        parser: P,
+        stop: &[TokenKind],
+        newline: bool,
+    ) -> Result<T, TextSpan> {
+        let start = self.lexer.peek().span().start();
+        let closers = self.closers.len();
+
+        let result = parser(self);
+        self.closers.truncate(closers);
+
+        result.ok_or_else(|| {
+            self.synchronize(stop, newline);
+            TextSpan::new(start, usize::max(start, self.lexer.previous_end()))
+        })
+    }
+
+    pub fn parse_module(&mut self) -> Module {
+        let mut items = vec![];
+        let mut globals = vec![];
+
+        while *self.lexer.peek().kind() != TokenKind::EOF {
+            let start = self.lexer.peek().span().start();
+            let item = self.recover(
+                |this| {
+                    // Doc comments go before `export`
+                    let doc = this.lexer.peek().doc().clone();
+                    let exported = if *this.lexer.peek().kind() == TokenKind::Export {
+                        this.lexer.eat(); // export
+                        true
+                    } else {
+                        false
+                    };
+
+                    match this.lexer.peek().kind() {
+                        TokenKind::Import => {
+                            Some(Ok((exported, Item::Import(this.parse_import()?))))
+                        }
+                        TokenKind::Function => {
+                            Some(Ok((exported, Item::Function(this.parse_function(doc)?))))
+                        }
+                        TokenKind::Type | TokenKind::Struct => {
+                            Some(Ok((exported, Item::TypeDecls(this.parse_type_decl(doc)?))))
+                        }
+                        TokenKind::Let => Some(Err((exported, this.parse_global(doc)?))),
+                        _ => {
+                            this.unexpected()
+                                .expected("import, function, type, struct or global");
+                            None
+                        }
+                    }
+                },
+                // Globals begin with a `let`, which isn't an item start anywhere else
+                &[TokenKind::Let],
+                false,
+            );
+
+            match item {
+                Ok(Ok(item)) => items.push(item),
+                Ok(Err(global)) => globals.push(global),
+                Err(_) => {
+                    // Make sure to not get stuck on something that isn't the start of an item
+                    if self.lexer.peek().span().start() == start {
+                        self.lexer.eat();
+                    }
+                }
+            }
+        }
+
+        Module::new(items, globals)
+    }
+
+    /// Parses a whole package, whose root module is all of the source code
+    pub fn parse_package(&mut self) -> Package {
+        let root = self.parse_module();
+        Package::new(Config::default(), root)
+    }
+
+    fn parse_import(&mut self) -> Option<Import> {
+        let import_token = match_token!(self, Import);
+        let path = self.parse_path()?;
+
+        Some(Import::new(import_token, path))
+    }
+
+    fn parse_function(&mut self, doc: Doc) -> Option<Function> {
+        let fn_token = match_token!(self, Function);
+        let ident = self.parse_ident()?;
+
+        match_token!(self, LeftParen);
+        let (args, right_paren_span) = self.parse_many(
+            Self::parse_typed_ident,
+            TokenKind::RightParen,
+            Some(TokenKind::Comma),
+        )?;
+
+        let ret_type = if *self.lexer.peek().kind() == TokenKind::RightArrow {
+            self.lexer.eat(); // ->
+            self.parse_type()
+        } else {
+            let end = right_paren_span.end();
+            Type::new(TypeInner::Tuple(vec![]), TextSpan::new(end, end))
+        };
+
+        // fn name() = expression  or  fn name() { block }
+        let body = match self.lexer.peek().kind() {
+            TokenKind::Equal => {
+                let equal = self.lexer.eat(); // =
+                let body = self.parse_expr();
+                if let ExprInner::Block(_) = body.inner {
+                    let removal = TextSpan::new(equal.span().start(), body.span().start());
+                    self.diagnostics
+                        .equal_before_block_body(*equal.span(), removal);
+                }
+                body
             }
+            TokenKind::LeftCurly => self.parse_expr(),
+            _ => {
+                self.unexpected()
+                    .expected_tokens(&[TokenKind::Equal, TokenKind::LeftCurly]);
+                return None;
+            }
+        };
+
+        Some(Function::new(fn_token, ident, args, ret_type, body, doc))
+    }
+
+    /// Parses both `type Name = type` and the shorthand `struct Name { fields }`
+    fn parse_type_decl(&mut self, doc: Doc) -> Option<TypeDecl> {
+        if *self.lexer.peek().kind() == TokenKind::Struct {
+            let struct_token = self.lexer.eat(); // struct
+            let ident = self.parse_ident()?;
+
+            match_token!(self, LeftCurly);
+            let (fields, right_curly_span) = self.parse_many(
+                Self::parse_typed_ident,
+                TokenKind::RightCurly,
+                Some(TokenKind::Comma),
+            )?;
+
+            let span: TextSpan = (struct_token.span(), &right_curly_span).into();
+            let ty = Type::new(TypeInner::Struct(fields), span);
+            return Some(TypeDecl::new(struct_token, ident, ty, doc));
+        }
+
+        let type_token = match_token!(self, Type);
+        let ident = self.parse_ident()?;
+        match_token!(self, Equal);
+        let ty = self.parse_type();
 
-            let colon_colon = self.lexer.peek();
-            if *colon_colon.kind() == TokenKind::ColonColon && !colon_colon.whitespace_after() {
+        Some(TypeDecl::new(type_token, ident, ty, doc))
+    }
+
+    fn parse_global(&mut self, doc: Doc) -> Option<Global> {
+        let let_token = match_token!(self, Let);
+        let ident = self.parse_ident()?;
+        let ty = if *self.lexer.peek().kind() == TokenKind::Colon {
+            self.lexer.eat(); // :
+            Some(self.parse_type())
+        } else {
+            None
+        };
+        match_token!(self, Equal);
+        let value = self.parse_expr();
+
+        Some(Global::new(let_token, ident, ty, value, doc))
+    }
+
+    /// Parses `name: type`, as in struct fields and function arguments
+    fn parse_typed_ident(&mut self) -> Option<(Ident, Type)> {
+        let ident = self.parse_ident()?;
+
+        match_token!(self, Colon);
+
+        let ty = self.parse_type();
+
+        Some((ident, ty))
+    }
+
+    pub fn parse_ident(&mut self) -> Option<Ident> {
+        let ident_token = match_token!(
+            self,
+            TokenKind::Ident(_),
+            TokenKind::Ident(Symbol::intern(""))
+        );
+        match ident_token.kind() {
+            TokenKind::Ident(ident) => Some(Ident::new(*ident, *ident_token.span())),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn parse_path(&mut self) -> Option<Path> {
+        let mut path = vec![];
+        loop {
+            path.push(self.parse_ident()?);
+
+            // `::` only continues the path if it's followed by another part of it
+            if *self.lexer.peek().kind() == TokenKind::ColonColon
+                && matches!(self.lexer.peek_nth(1).kind(), TokenKind::Ident(_))
+            {
                 self.lexer.eat(); // ::
             } else {
                 break;
@@ -66,7 +316,22 @@ impl<'a> Parser<'a> {
         Some(Path::new(path))
     }
 
-    pub fn parse_type(&mut self) -> Option<Type> {
+    pub fn parse_type(&mut self) -> Type {
+        let stop = [
+            TokenKind::Comma,
+            TokenKind::Equal,
+            TokenKind::RightArrow,
+            TokenKind::Semicolon,
+            TokenKind::DotDot,
+            TokenKind::LeftCurly,
+        ];
+        match self.recover(Self::parse_type_, &stop, true) {
+            Ok(ty) => ty,
+            Err(span) => Type::new(TypeInner::Error, span),
+        }
+    }
+
+    fn parse_type_(&mut self) -> Option<Type> {
         let token = self.lexer.peek();
         let ty = match token.kind() {
             TokenKind::Ident(_) => {
@@ -76,7 +341,7 @@ impl<'a> Parser<'a> {
             }
             TokenKind::Amp => {
                 let amp_token = self.lexer.eat(); // &
-                let inner = self.parse_type()?;
+                let inner = self.parse_type();
                 if let Type {
                     inner: TypeInner::InPlaceDynamicArray(ty),
                     span,
@@ -92,7 +357,7 @@ impl<'a> Parser<'a> {
                 let left_paren = self.lexer.eat();
 
                 let (types, right_paren_span) = self.parse_many(
-                    Self::parse_type,
+                    |this| Some(this.parse_type()),
                     TokenKind::RightParen,
                     Some(TokenKind::Comma),
                 )?;
@@ -104,7 +369,7 @@ impl<'a> Parser<'a> {
             }
             TokenKind::LeftSquare => {
                 let left_square = self.lexer.eat();
-                let inner = box self.parse_type()?;
+                let inner = box self.parse_type();
 
                 let next = self.lexer.peek();
                 let ty = match next.kind() {
@@ -112,20 +377,18 @@ impl<'a> Parser<'a> {
                         self.lexer.eat(); // ..
                         TypeInner::DynamicArray(inner)
                     }
-                    TokenKind::Asterisk => {
-                        self.lexer.eat(); // *
-                        let size = self.parse_expr()?;
+                    TokenKind::Semicolon => {
+                        self.lexer.eat(); // ;
+                        let size = self.parse_expr();
                         TypeInner::SizedArray(inner, size)
                     }
                     TokenKind::RightSquare => TypeInner::InPlaceDynamicArray(inner),
                     _ => {
-                        self.diagnostics
-                            .unexpected_token(self.lexer.eat())
-                            .expected_tokens(&[
-                                TokenKind::RightSquare,
-                                TokenKind::DotDot,
-                                TokenKind::Asterisk,
-                            ]);
+                        self.unexpected().expected_tokens(&[
+                            TokenKind::RightSquare,
+                            TokenKind::DotDot,
+                            TokenKind::Semicolon,
+                        ]);
                         return None;
                     }
                 };
@@ -135,33 +398,39 @@ impl<'a> Parser<'a> {
             }
             TokenKind::Function => {
                 let fn_token = self.lexer.eat();
-                let args = match self.parse_type()? {
+                let args = self.parse_type();
+                let args_end = args.span().end();
+                let args = match args {
                     Type {
                         inner: TypeInner::Tuple(args),
                         ..
                     } => args,
                     arg => vec![arg],
                 };
-                match_token!(self, RightArrow);
 
-                let returns = box self.parse_type()?;
+                // fn args  is short for  fn args -> ()
+                let returns = if *self.lexer.peek().kind() == TokenKind::RightArrow {
+                    self.lexer.eat(); // ->
+                    box self.parse_type()
+                } else {
+                    box Type::new(TypeInner::Tuple(vec![]), TextSpan::new(args_end, args_end))
+                };
 
                 let span: TextSpan = (fn_token.span(), returns.span()).into();
                 Type::new(TypeInner::Function { args, returns }, span)
             }
             TokenKind::LeftCurly => {
                 let left_curly = self.lexer.eat();
-                let yields = box self.parse_type()?;
+                let yields = box self.parse_type();
                 let next = self.lexer.peek();
                 let returns = match next.kind() {
                     TokenKind::Comma => {
                         self.lexer.eat();
-                        Some(box self.parse_type()?)
+                        Some(box self.parse_type())
                     }
                     TokenKind::RightCurly => None,
                     _ => {
-                        self.diagnostics
-                            .unexpected_token(self.lexer.eat())
+                        self.unexpected()
                             .expected_tokens(&[TokenKind::Comma, TokenKind::RightCurly]);
                         return None;
                     }
@@ -177,39 +446,53 @@ impl<'a> Parser<'a> {
                 let struct_token = self.lexer.eat(); // struct
                 match_token!(self, LeftCurly);
 
-                fn parse_field(this: &mut Parser) -> Option<(Ident, Type)> {
-                    // TODO: clean this up if possible
-                    let mut ident_token = this.lexer.eat();
-                    let name = match ident_token.take_kind() {
-                        TokenKind::Ident(name) => name,
-                        _ => {
-                            this.diagnostics
-                                .unexpected_token(ident_token)
-                                .expected_token(&TokenKind::Ident(String::new()));
-                            return None;
-                        }
-                    };
-                    let ident = Ident::new(name, *ident_token.span());
+                let (fields, right_curly_span) = self.parse_many(
+                    Self::parse_typed_ident,
+                    TokenKind::RightCurly,
+                    Some(TokenKind::Comma),
+                )?;
+
+                Type::new(
+                    TypeInner::Struct(fields),
+                    (struct_token.span(), &right_curly_span),
+                )
+            }
+            TokenKind::Enum => {
+                let enum_token = self.lexer.eat(); // enum
+                let ident = if let TokenKind::Ident(_) = self.lexer.peek().kind() {
+                    Some(self.parse_ident()?)
+                } else {
+                    None
+                };
+                match_token!(self, LeftCurly);
 
-                    match_token!(this, Colon);
+                fn parse_variant(this: &mut Parser) -> Option<(Ident, Option<Type>)> {
+                    let ident = this.parse_ident()?;
 
-                    let ty = this.parse_type()?;
+                    let payload = match this.lexer.peek().kind() {
+                        // Name(type1, type2)
+                        TokenKind::LeftParen => Some(this.parse_type()),
+                        // Name: struct { .. }  or  Name: enum { .. }
+                        TokenKind::Colon => {
+                            this.lexer.eat(); // :
+                            Some(this.parse_type())
+                        }
+                        _ => None,
+                    };
 
-                    Some((ident, ty))
+                    Some((ident, payload))
                 }
 
-                let (fields, right_curly_span) =
-                    self.parse_many(parse_field, TokenKind::RightCurly, Some(TokenKind::Comma))?;
+                let (variants, right_curly_span) =
+                    self.parse_many(parse_variant, TokenKind::RightCurly, Some(TokenKind::Comma))?;
 
                 Type::new(
-                    TypeInner::Struct(fields),
-                    (struct_token.span(), &right_curly_span),
+                    TypeInner::Enum { ident, variants },
+                    (enum_token.span(), &right_curly_span),
                 )
             }
             _ => {
-                self.diagnostics
-                    .unexpected_token(self.lexer.eat())
-                    .expected("type");
+                self.unexpected().expected("type");
                 return None;
             }
         };
@@ -217,11 +500,119 @@ impl<'a> Parser<'a> {
         Some(ty)
     }
 
-    pub fn parse_expr(&mut self) -> Option<Expr> {
-        self.parse_expr_(0)
+    pub fn parse_expr(&mut self) -> Expr {
+        let stop = [
+            TokenKind::Comma,
+            TokenKind::Semicolon,
+            TokenKind::FatArrow,
+            TokenKind::Else,
+        ];
+        match self.recover(Self::parse_range, &stop, true) {
+            Ok(expr) => expr,
+            Err(span) => Expr::new(ExprInner::Error, span),
+        }
     }
 
     fn parse_expr_(&mut self, prec_lvl: usize) -> Option<Expr> {
+        let mut expr = if let Some(operator) = UnaryOperator::new(self.lexer.peek()) {
+            let operator_token = self.lexer.eat();
+            let operand = box self.parse_expr_(operator.precedence())?;
+
+            let span: TextSpan = (operator_token.span(), operand.span()).into();
+            Expr::new(ExprInner::Unary { operator, operand }, span)
+        } else {
+            let primary = self.parse_primary_expr()?;
+            self.parse_postfix_expr(primary)?
+        };
+
+        // Set when `expr` is a comparison built by this loop, as those don't chain
+        let mut comparison: Option<(usize, TextSpan)> = None;
+
+        while let Some(operator) = BinaryOperator::new(self.lexer.peek()) {
+            let precedence = operator.precedence();
+            if precedence < prec_lvl || precedence == prec_lvl && operator.left_assoc() {
+                break;
+            }
+            let operator_token = self.lexer.eat();
+
+            if let Some((comparison_precedence, previous)) = comparison {
+                if operator.is_comparison() && comparison_precedence == precedence {
+                    self.diagnostics
+                        .chained_comparison(*operator_token.span(), previous);
+                }
+            }
+            comparison = if operator.is_comparison() {
+                Some((precedence, *operator_token.span()))
+            } else {
+                None
+            };
+
+            let right = box self.parse_expr_(precedence)?;
+
+            let span: TextSpan = (expr.span(), right.span()).into();
+            expr = Expr::new(
+                ExprInner::Binary {
+                    left: box expr,
+                    operator,
+                    right,
+                },
+                span,
+            );
+        }
+
+        Some(expr)
+    }
+
+    fn parse_primary_expr(&mut self) -> Option<Expr> {
+        if let TokenKind::Ident(_) = self.lexer.peek().kind() {
+            let path = self.parse_path()?;
+            if self.is_struct_expr() {
+                return self.parse_struct_expr(path);
+            }
+            let span = path.span();
+            return Some(Expr::new(ExprInner::Path(path), span));
+        }
+
+        if self.is_function_item() {
+            // A function on a new line outside of any brackets is simply the next item
+            if *self.lexer.peek().newline_before() && self.closers.is_empty() {
+                self.unexpected().expected("expression");
+                return None;
+            }
+            let fn_token = self.lexer.eat(); // fn
+            let ident = self.parse_ident()?;
+            self.diagnostics
+                .function_item_in_expression((fn_token.span(), &ident.span()).into());
+            return None;
+        }
+
+        let next = self.lexer.peek();
+        let starts_expr = match next.kind() {
+            TokenKind::Function => true,
+            kind => matches!(
+                kind,
+                TokenKind::True
+                    | TokenKind::False
+                    | TokenKind::Null
+                    | TokenKind::String(_)
+                    | TokenKind::InterpolatedString(_)
+                    | TokenKind::Char(_)
+                    | TokenKind::Integer(..)
+                    | TokenKind::Float(..)
+                    | TokenKind::LeftParen
+                    | TokenKind::LeftSquare
+                    | TokenKind::At
+                    | TokenKind::Loop
+                    | TokenKind::LeftCurly
+                    | TokenKind::Match
+                    | TokenKind::If
+            ),
+        };
+        if !starts_expr {
+            self.unexpected().expected("expression");
+            return None;
+        }
+
         let mut token = self.lexer.eat();
         let expr = match token.take_kind() {
             TokenKind::True => Expr::new(ExprInner::Literal(Literal::Bool(true)), *token.span()),
@@ -230,15 +621,30 @@ impl<'a> Parser<'a> {
             TokenKind::String(s) => {
                 Expr::new(ExprInner::Literal(Literal::String(s)), *token.span())
             }
-            TokenKind::Integer(val) => {
-                Expr::new(ExprInner::Literal(Literal::Integer(val)), *token.span())
-            }
-            TokenKind::Float(val) => {
-                Expr::new(ExprInner::Literal(Literal::Float(val)), *token.span())
+            TokenKind::InterpolatedString(parts) => {
+                let parts = parts
+                    .into_iter()
+                    .map(|part| match part {
+                        StringPart::Literal(s) => InterpolatedPart::Literal(s),
+                        StringPart::Interpolation(span) => {
+                            InterpolatedPart::Expr(self.parse_interpolation(span))
+                        }
+                    })
+                    .collect();
+                Expr::new(ExprInner::Interpolated(parts), *token.span())
             }
+            TokenKind::Char(c) => Expr::new(ExprInner::Literal(Literal::Char(c)), *token.span()),
+            TokenKind::Integer(val, suffix) => Expr::new(
+                ExprInner::Literal(Literal::Integer(val, suffix)),
+                *token.span(),
+            ),
+            TokenKind::Float(val, suffix) => Expr::new(
+                ExprInner::Literal(Literal::Float(val, suffix)),
+                *token.span(),
+            ),
             TokenKind::LeftParen => {
                 let (exprs, right_paren_span) = self.parse_many(
-                    Self::parse_expr,
+                    |this| Some(this.parse_expr()),
                     TokenKind::RightParen,
                     Some(TokenKind::Comma),
                 )?;
@@ -250,23 +656,155 @@ impl<'a> Parser<'a> {
                     Expr::new(ExprInner::Tuple(exprs), (token.span(), &right_paren_span))
                 }
             }
+            TokenKind::LeftSquare => {
+                let mut values = vec![];
+                let mut size = None;
+                let mut dynamic = false;
+                self.closers.push(TokenKind::RightSquare);
+                loop {
+                    match self.lexer.peek().kind() {
+                        TokenKind::RightSquare => break,
+                        TokenKind::DotDot => {
+                            self.lexer.eat(); // ..
+                            dynamic = true;
+                            break;
+                        }
+                        _ => values.push(self.parse_expr()),
+                    }
+                    let next_start = self.lexer.peek().span().start();
+                    match self.lexer.peek().kind() {
+                        TokenKind::Comma => {
+                            self.lexer.eat(); // ,
+                        }
+                        TokenKind::Semicolon if values.len() == 1 => {
+                            self.lexer.eat(); // ;
+                            size = Some(box self.parse_expr());
+                            break;
+                        }
+                        TokenKind::RightSquare => break,
+                        // Recovering from an error in the value may have stopped right here
+                        _ if self.last_unexpected == Some(next_start) => {
+                            return None;
+                        }
+                        _ => {
+                            let d = self.unexpected();
+                            if values.len() == 1 {
+                                d.expected_tokens(&[
+                                    TokenKind::Comma,
+                                    TokenKind::Semicolon,
+                                    TokenKind::RightSquare,
+                                ]);
+                            } else {
+                                d.expected_tokens(&[TokenKind::Comma, TokenKind::RightSquare]);
+                            }
+                            return None;
+                        }
+                    }
+                }
+                self.closers.pop();
+                let right_square = match_token!(self, RightSquare);
+
+                let inner = if dynamic {
+                    ExprInner::DynamicArray(values)
+                } else {
+                    ExprInner::SizedArray(values, size)
+                };
+                Expr::new(inner, (token.span(), right_square.span()))
+            }
+            TokenKind::At => {
+                if !matches!(
+                    self.lexer.peek().kind(),
+                    TokenKind::LeftSquare | TokenKind::LeftParen
+                ) {
+                    self.unexpected()
+                        .expected_tokens(&[TokenKind::LeftSquare, TokenKind::LeftParen]);
+                    return None;
+                }
+
+                let next = self.lexer.eat();
+                match next.kind() {
+                    TokenKind::LeftSquare => {
+                        let (values, right_square_span) = self.parse_many(
+                            |this| Some(this.parse_expr()),
+                            TokenKind::RightSquare,
+                            Some(TokenKind::Comma),
+                        )?;
+
+                        Expr::new(ExprInner::List(values), (token.span(), &right_square_span))
+                    }
+                    _ => {
+                        let (values, right_paren_span) = self.parse_many(
+                            |this| Some(this.parse_expr()),
+                            TokenKind::RightParen,
+                            Some(TokenKind::Comma),
+                        )?;
+
+                        Expr::new(ExprInner::Tuple(values), (token.span(), &right_paren_span))
+                    }
+                }
+            }
+            TokenKind::Function => {
+                let args = match self.lexer.peek().kind() {
+                    TokenKind::LeftParen => {
+                        self.lexer.eat(); // (
+                        let (args, _) = self.parse_many(
+                            Self::parse_closure_arg,
+                            TokenKind::RightParen,
+                            Some(TokenKind::Comma),
+                        )?;
+                        args
+                    }
+                    TokenKind::Equal => vec![],
+                    _ => vec![self.parse_closure_arg()?],
+                };
+                match_token!(self, Equal);
+                let body = box self.parse_expr();
+
+                let span: TextSpan = (token.span(), body.span()).into();
+                Expr::new(ExprInner::Closure { args, body }, span)
+            }
             TokenKind::Loop => {
-                let expr = self.parse_expr()?;
-                let expr_span = *expr.span();
-                Expr::new(expr.inner, (token.span(), &expr_span))
+                let body = box self.parse_expr();
+                let span: TextSpan = (token.span(), body.span()).into();
+                Expr::new(ExprInner::Loop(body), span)
             }
             TokenKind::LeftCurly => {
                 let (stmts, right_curly_span) =
-                    self.parse_many(Self::parse_stmt, TokenKind::RightParen, None)?;
+                    self.parse_many(|this| Some(this.parse_stmt()), TokenKind::RightCurly, None)?;
 
                 Expr::new(ExprInner::Block(stmts), (token.span(), &right_curly_span))
             }
+            TokenKind::Match => {
+                let on = box self.parse_expr();
+                match_token!(self, LeftCurly);
+
+                fn parse_arm(this: &mut Parser) -> Option<(Pattern, Expr)> {
+                    let pattern = this.parse_pattern()?;
+                    match_token!(this, FatArrow);
+                    let value = this.parse_expr();
+
+                    // Arms are usually separated by newlines, but commas are allowed as well
+                    if *this.lexer.peek().kind() == TokenKind::Comma {
+                        this.lexer.eat(); // ,
+                    }
+
+                    Some((pattern, value))
+                }
+
+                let (arms, right_curly_span) =
+                    self.parse_many(parse_arm, TokenKind::RightCurly, None)?;
+
+                Expr::new(
+                    ExprInner::Match { on, arms },
+                    (token.span(), &right_curly_span),
+                )
+            }
             TokenKind::If => {
-                let condition = box self.parse_expr()?;
-                let then = box self.parse_stmt()?;
+                let condition = box self.parse_condition();
+                let then = box self.parse_stmt();
                 let els = if *self.lexer.peek().kind() == TokenKind::Else {
                     self.lexer.eat(); // else
-                    Some(box self.parse_stmt()?)
+                    Some(box self.parse_stmt())
                 } else {
                     None
                 };
@@ -282,59 +820,583 @@ impl<'a> Parser<'a> {
                     span,
                 )
             }
-            _ => {
-                self.diagnostics
-                    .unexpected_token(token)
-                    .expected("expression");
+            _ => unreachable!(),
+        };
+
+        Some(expr)
+    }
+
+    /// Parses the condition of an `if` or `while`, or what a `for` iterates over. The body after
+    /// it may be introduced with a colon, as in `if a == b :{ .. }`, so there `:{` isn't taken
+    /// as a struct literal or the end of a range.
+    fn parse_condition(&mut self) -> Expr {
+        let outer = self.condition.replace(self.closers.len());
+        let condition = self.parse_expr();
+        self.condition = outer;
+
+        if *self.lexer.peek().kind() == TokenKind::Colon
+            && *self.lexer.peek_nth(1).kind() == TokenKind::LeftCurly
+        {
+            self.lexer.eat(); // :
+        }
+        condition
+    }
+
+    /// Whether the next tokens are `fn name(`, which can only start a function item, as
+    /// closures are written `fn arg = body` or `fn (args) = body`
+    fn is_function_item(&mut self) -> bool {
+        *self.lexer.peek().kind() == TokenKind::Function
+            && matches!(self.lexer.peek_nth(1).kind(), TokenKind::Ident(_))
+            && *self.lexer.peek_nth(2).kind() == TokenKind::LeftParen
+    }
+
+    /// Parses the expression of a `%( )` in a string, which the lexer has only skipped past
+    fn parse_interpolation(&mut self, span: TextSpan) -> Expr {
+        // The parser would only find the end of nothing, rather than what's missing
+        if self.lexer.source_text(span).trim().is_empty() {
+            let span = TextSpan::new(span.start() - 2, span.end() + 1); // %( )
+            self.diagnostics.empty_interpolation(span);
+            return Expr::new(ExprInner::Error, span);
+        }
+        // Errors in its tokens were reported when the lexer skipped past them
+        let mut parser = Parser::new(self.lexer.sub_lexer(span));
+        let expr = parser.parse_expr();
+        if *parser.lexer.peek().kind() != TokenKind::EOF {
+            parser.unexpected().expected_token(&TokenKind::RightParen);
+        }
+        self.diagnostics.append(&mut parser.diagnostics);
+        expr
+    }
+
+    fn parse_closure_arg(&mut self) -> Option<(Ident, Option<Type>)> {
+        let ident = self.parse_ident()?;
+        let ty = if *self.lexer.peek().kind() == TokenKind::Colon {
+            self.lexer.eat(); // :
+            Some(self.parse_type())
+        } else {
+            None
+        };
+
+        Some((ident, ty))
+    }
+
+    /// Whether the next tokens are the `@{` or `:{` of a struct literal following a path
+    fn is_struct_expr(&mut self) -> bool {
+        match self.lexer.peek().kind() {
+            TokenKind::At => true,
+            TokenKind::Colon => {
+                !self.lexer.peek().newline_before()
+                    && *self.lexer.peek_nth(1).kind() == TokenKind::LeftCurly
+                    && self.condition != Some(self.closers.len())
+            }
+            _ => false,
+        }
+    }
+
+    /// Parses the `@{ field: value, .. }` or `:{ field: value, .. }` following the path of a
+    /// struct literal
+    fn parse_struct_expr(&mut self, path: Path) -> Option<Expr> {
+        self.lexer.eat(); // @ or :
+        match_token!(self, LeftCurly);
+
+        fn parse_field(this: &mut Parser) -> Option<(Ident, Expr)> {
+            let ident = this.parse_ident()?;
+
+            match_token!(this, Colon);
+
+            let value = this.parse_expr();
+
+            Some((ident, value))
+        }
 
+        let (values, right_curly_span) =
+            self.parse_many(parse_field, TokenKind::RightCurly, Some(TokenKind::Comma))?;
+
+        let span: TextSpan = (&path.span(), &right_curly_span).into();
+        Some(Expr::new(ExprInner::Struct { path, values }, span))
+    }
+
+    /// Parses calls, indexing and field accesses following `expr`. A parenthesis or square
+    /// bracket on a new line starts a new statement rather than calling or indexing.
+    fn parse_postfix_expr(&mut self, mut expr: Expr) -> Option<Expr> {
+        loop {
+            let next = self.lexer.peek();
+            let newline_before = *next.newline_before();
+            expr = match next.kind() {
+                TokenKind::LeftParen if !newline_before => {
+                    self.lexer.eat(); // (
+                    let (args, right_paren_span) = self.parse_many(
+                        |this| Some(this.parse_expr()),
+                        TokenKind::RightParen,
+                        Some(TokenKind::Comma),
+                    )?;
+
+                    let span: TextSpan = (expr.span(), &right_paren_span).into();
+                    Expr::new(
+                        ExprInner::Evoc {
+                            func: box expr,
+                            args,
+                        },
+                        span,
+                    )
+                }
+                TokenKind::LeftSquare if !newline_before => {
+                    self.lexer.eat(); // [
+                    self.closers.push(TokenKind::RightSquare);
+                    let index = box self.parse_expr();
+                    self.closers.pop();
+                    let right_square = match_token!(self, RightSquare);
+
+                    let span: TextSpan = (expr.span(), right_square.span()).into();
+                    Expr::new(
+                        ExprInner::Indexing {
+                            into: box expr,
+                            index,
+                        },
+                        span,
+                    )
+                }
+                TokenKind::Dot => {
+                    self.lexer.eat(); // .
+                    let field = self.parse_ident()?;
+
+                    let span: TextSpan = (expr.span(), &field.span()).into();
+                    Expr::new(
+                        ExprInner::FieldAccess {
+                            on: box expr,
+                            field,
+                        },
+                        span,
+                    )
+                }
+                _ => break,
+            };
+        }
+
+        Some(expr)
+    }
+
+    /// Parses an expression that may be a range: `start:end`, `start:=end` which includes its
+    /// end, or the open `start:`, `:end` and `:`. Ranges bind looser than any operator. The
+    /// colons of `let x: T` and `@{ field: value }` are eaten before their value is parsed, so
+    /// they never end up here. A colon on a new line doesn't continue the expression, since it
+    /// can start the struct pattern of the next match arm, and neither does `:{`, which begins
+    /// a struct literal or the body of an `if`.
+    fn parse_range(&mut self) -> Option<Expr> {
+        let is_range_colon = |this: &mut Self| {
+            *this.lexer.peek().kind() == TokenKind::Colon
+                && *this.lexer.peek_nth(1).kind() != TokenKind::LeftCurly
+        };
+
+        let start = if is_range_colon(self) {
+            None
+        } else {
+            Some(self.parse_expr_(0)?)
+        };
+
+        if !is_range_colon(self) || start.is_some() && *self.lexer.peek().newline_before() {
+            return start;
+        }
+        let colon = self.lexer.eat(); // :
+        let mut last = *colon.span();
+
+        let next = self.lexer.peek();
+        let inclusive =
+            *next.kind() == TokenKind::Equal && next.span().start() == colon.span().end();
+        if inclusive {
+            last = *self.lexer.eat().span(); // =
+        }
+
+        // The end can only be left out if nothing follows that could continue the range
+        let next = self.lexer.peek();
+        let end = if !inclusive
+            && (*next.newline_before()
+                || matches!(
+                    next.kind(),
+                    TokenKind::RightSquare
+                        | TokenKind::RightParen
+                        | TokenKind::RightCurly
+                        | TokenKind::Comma
+                        | TokenKind::Semicolon
+                        | TokenKind::FatArrow
+                        | TokenKind::EOF
+                )) {
+            None
+        } else {
+            let end = self.parse_expr_(0)?;
+            last = *end.span();
+            Some(box end)
+        };
+
+        let first = start.as_ref().map_or(*colon.span(), |start| *start.span());
+        Some(Expr::new(
+            ExprInner::Range {
+                start: start.map(Box::new),
+                end,
+                inclusive,
+            },
+            (&first, &last),
+        ))
+    }
+
+    pub fn parse_pattern(&mut self) -> Option<Pattern> {
+        if let TokenKind::Ident(ident) = self.lexer.peek().kind() {
+            if ident.as_str() == "_" {
+                let underscore = self.lexer.eat();
+                return Some(Pattern::new(PatternInner::Wildcard, *underscore.span()));
+            }
+
+            let mut path = self.parse_path()?;
+            let next = self.lexer.peek();
+            let has_payload = !next.newline_before()
+                && matches!(
+                    next.kind(),
+                    TokenKind::Ident(_)
+                        | TokenKind::LeftParen
+                        | TokenKind::Colon
+                        | TokenKind::String(_)
+                        | TokenKind::Char(_)
+                        | TokenKind::Integer(..)
+                        | TokenKind::Float(..)
+                        | TokenKind::Minus
+                        | TokenKind::True
+                        | TokenKind::False
+                        | TokenKind::Null
+                );
+
+            // A lone identifier binds a name, anything longer refers to an enum variant
+            if !has_payload && path.len() == 1 {
+                let ident = path.0.pop().unwrap();
+                let span = ident.span();
+                return Some(Pattern::new(PatternInner::Binding(ident), span));
+            }
+
+            let payload = if has_payload {
+                Some(box self.parse_pattern()?)
+            } else {
+                None
+            };
+            let last = payload
+                .as_ref()
+                .map_or(path.span(), |payload| *payload.span());
+            let span: TextSpan = (&path.span(), &last).into();
+            return Some(Pattern::new(PatternInner::Variant { path, payload }, span));
+        }
+
+        if *self.lexer.peek().kind() == TokenKind::Minus {
+            let minus = self.lexer.eat(); // -
+            if !matches!(
+                self.lexer.peek().kind(),
+                TokenKind::Integer(..) | TokenKind::Float(..)
+            ) {
+                self.unexpected().expected("number");
                 return None;
             }
+            let mut number = self.lexer.eat();
+            let literal = match number.take_kind() {
+                TokenKind::Integer(val, suffix) => Literal::Integer(val, suffix),
+                TokenKind::Float(val, suffix) => Literal::Float(val, suffix),
+                _ => unreachable!(),
+            };
+
+            let span: TextSpan = (minus.span(), number.span()).into();
+            return Some(Pattern::new(PatternInner::Negative(literal), span));
+        }
+
+        if !matches!(
+            self.lexer.peek().kind(),
+            TokenKind::True
+                | TokenKind::False
+                | TokenKind::Null
+                | TokenKind::String(_)
+                | TokenKind::Char(_)
+                | TokenKind::Integer(..)
+                | TokenKind::Float(..)
+                | TokenKind::LeftParen
+                | TokenKind::Colon
+        ) {
+            self.unexpected().expected("pattern");
+            return None;
+        }
+
+        let mut token = self.lexer.eat();
+        let pattern = match token.take_kind() {
+            TokenKind::True => PatternInner::Literal(Literal::Bool(true)),
+            TokenKind::False => PatternInner::Literal(Literal::Bool(false)),
+            TokenKind::Null => PatternInner::Literal(Literal::Null),
+            TokenKind::String(s) => PatternInner::Literal(Literal::String(s)),
+            TokenKind::Char(c) => PatternInner::Literal(Literal::Char(c)),
+            TokenKind::Integer(val, suffix) => PatternInner::Literal(Literal::Integer(val, suffix)),
+            TokenKind::Float(val, suffix) => PatternInner::Literal(Literal::Float(val, suffix)),
+            TokenKind::LeftParen => {
+                let (patterns, right_paren_span) = self.parse_many(
+                    Self::parse_pattern,
+                    TokenKind::RightParen,
+                    Some(TokenKind::Comma),
+                )?;
+
+                let span: TextSpan = (token.span(), &right_paren_span).into();
+                return Some(Pattern::new(PatternInner::Tuple(patterns), span));
+            }
+            TokenKind::Colon => {
+                match_token!(self, LeftCurly);
+
+                fn parse_field(this: &mut Parser) -> Option<(Ident, Option<Pattern>)> {
+                    let ident = this.parse_ident()?;
+                    let pattern = if *this.lexer.peek().kind() == TokenKind::Colon {
+                        this.lexer.eat(); // :
+                        Some(this.parse_pattern()?)
+                    } else {
+                        None
+                    };
+
+                    Some((ident, pattern))
+                }
+
+                let (fields, right_curly_span) =
+                    self.parse_many(parse_field, TokenKind::RightCurly, Some(TokenKind::Comma))?;
+
+                let span: TextSpan = (token.span(), &right_curly_span).into();
+                return Some(Pattern::new(PatternInner::Struct(fields), span));
+            }
+            _ => unreachable!(),
         };
 
-        Some(expr)
+        Some(Pattern::new(pattern, *token.span()))
     }
 
-    pub fn parse_stmt(&mut self) -> Option<Stmt> {
-        unimplemented!()
+    pub fn parse_stmt(&mut self) -> Stmt {
+        match self.recover(Self::parse_stmt_, &[TokenKind::Else], true) {
+            Ok(stmt) => stmt,
+            Err(span) => Stmt::new(StmtInner::Error, span),
+        }
     }
 
+    fn parse_stmt_(&mut self) -> Option<Stmt> {
+        let token = self.lexer.peek();
+        let stmt = match token.kind() {
+            TokenKind::Let => {
+                let let_token = self.lexer.eat(); // let
+                let ident = self.parse_ident()?;
+                let ty = if *self.lexer.peek().kind() == TokenKind::Colon {
+                    self.lexer.eat(); // :
+                    Some(self.parse_type())
+                } else {
+                    None
+                };
+                match_token!(self, Equal);
+                let value = self.parse_expr();
+
+                let span: TextSpan = (let_token.span(), value.span()).into();
+                Stmt::new(StmtInner::Let { ident, ty, value }, span)
+            }
+            TokenKind::For => {
+                let for_token = self.lexer.eat(); // for
+                let i = self.parse_ident()?;
+                match_token!(self, In);
+                let iter = self.parse_condition();
+                let body = box self.parse_stmt();
+
+                let span: TextSpan = (for_token.span(), body.span()).into();
+                Stmt::new(StmtInner::For { i, iter, body }, span)
+            }
+            TokenKind::While => {
+                let while_token = self.lexer.eat(); // while
+                let condition = self.parse_condition();
+                let body = box self.parse_stmt();
+
+                let span: TextSpan = (while_token.span(), body.span()).into();
+                Stmt::new(StmtInner::While { condition, body }, span)
+            }
+            TokenKind::Return => {
+                let return_token = self.lexer.eat(); // return
+                let value = self.parse_stmt_value();
+
+                let span: TextSpan = match &value {
+                    Some(value) => (return_token.span(), value.span()).into(),
+                    None => *return_token.span(),
+                };
+                Stmt::new(StmtInner::Return(value), span)
+            }
+            TokenKind::Yield => {
+                let yield_token = self.lexer.eat(); // yield
+                let value = self.parse_expr();
+
+                let span: TextSpan = (yield_token.span(), value.span()).into();
+                Stmt::new(StmtInner::Yield(value), span)
+            }
+            TokenKind::Break => {
+                let break_token = self.lexer.eat(); // break
+                let value = self.parse_stmt_value();
+
+                let span: TextSpan = match &value {
+                    Some(value) => (break_token.span(), value.span()).into(),
+                    None => *break_token.span(),
+                };
+                Stmt::new(StmtInner::Break(value), span)
+            }
+            _ => {
+                let expr = self.parse_expr();
+                if let Some(op) = AssignmentOperator::new(self.lexer.peek()) {
+                    self.lexer.eat(); // the assignment operator
+                    let value = self.parse_expr();
+
+                    let span: TextSpan = (expr.span(), value.span()).into();
+                    Stmt::new(
+                        StmtInner::Assign {
+                            var: expr,
+                            op,
+                            value,
+                        },
+                        span,
+                    )
+                } else {
+                    let span = *expr.span();
+                    Stmt::new(StmtInner::Expr(expr), span)
+                }
+            }
+        };
+
+        Some(stmt)
+    }
+
+    /// Parses the optional value of `return` and `break`. There is no value if the statement is
+    /// followed by a newline or something that can't continue it, like `}`.
+    fn parse_stmt_value(&mut self) -> Option<Expr> {
+        let next = self.lexer.peek();
+        if *next.newline_before()
+            || matches!(
+                next.kind(),
+                TokenKind::RightCurly | TokenKind::RightParen | TokenKind::Else | TokenKind::EOF
+            )
+        {
+            None
+        } else {
+            Some(self.parse_expr())
+        }
+    }
+
+    /// Parses items until `finisher`, separated by `separator` if there is one. Items that fail to
+    /// parse are skipped, but a list that isn't finished before its enclosing list fails.
     fn parse_many<T, P: FnMut(&mut Self) -> Option<T>>(
         &mut self,
         mut parser: P,
         finisher: TokenKind,
-        mut separator: Option<TokenKind>,
+        separator: Option<TokenKind>,
     ) -> Option<(Vec<T>, TextSpan)> {
         let mut stuff = vec![];
+        let mut stop = vec![finisher.clone()];
+        stop.extend(separator.clone());
 
-        while *self.lexer.peek().kind() != finisher {
-            stuff.push(parser(self)?);
-            let next = self.lexer.peek();
-            if separator
-                .as_ref()
-                .map(|separator| *separator == *next.kind())
-                .unwrap_or(false)
-            {
-                self.lexer.eat();
-                continue;
+        self.closers.push(finisher.clone());
+        let finisher_span = loop {
+            if *self.lexer.peek().kind() == finisher {
+                break Some(*self.lexer.eat().span());
             }
-            if *next.kind() != finisher {
-                let d = self.diagnostics.unexpected_token(self.lexer.eat());
-                if let Some(separator) = separator.take() {
-                    d.expected_tokens(&[finisher, separator]);
-                } else {
-                    d.expected_token(&finisher);
+            let start = self.lexer.peek().span().start();
+            if self.next_closes_enclosing() {
+                // Don't complain about the same token twice
+                if self.last_unexpected != Some(start) {
+                    self.unexpected().expected_token(&finisher);
                 }
-                return None;
+                break None;
+            }
+            // A closing bracket that matches nothing was most likely meant to close this list
+            if separator.is_some() && is_stray_closer(self.lexer.peek().kind(), &self.closers) {
+                if self.last_unexpected != Some(start) {
+                    self.unexpected().expected_token(&finisher);
+                }
+                break Some(*self.lexer.eat().span());
             }
-        }
-        let finisher_token = self.lexer.eat();
-        if *finisher_token.kind() != finisher {
-            self.diagnostics
-                .unexpected_token(finisher_token)
-                .expected_token(&finisher);
-            return None;
-        }
 
-        Some((stuff, *finisher_token.span()))
+            // Error nodes count as parsed, so the item must also not have reported anything
+            let reported = self.diagnostics.len() + self.lexer.diagnostics().len();
+            let parsed = match parser(self) {
+                Some(item) => {
+                    stuff.push(item);
+                    self.diagnostics.len() + self.lexer.diagnostics().len() == reported
+                }
+                None => {
+                    self.synchronize(&stop, separator.is_none());
+                    false
+                }
+            };
+
+            // Without a separator, the items simply follow each other until the finisher
+            if let Some(separator) = &separator {
+                let next = self.lexer.peek().kind();
+                if next == separator {
+                    self.lexer.eat();
+                } else if *next != finisher
+                    && !is_stray_closer(next, &self.closers)
+                    && !self.next_closes_enclosing()
+                {
+                    // Don't complain about the same token twice
+                    let next_start = self.lexer.peek().span().start();
+                    if self.last_unexpected != Some(next_start) {
+                        let end = self.lexer.previous_end();
+                        // Before `name:` of a field or argument, the separator can't be anything
+                        // else, but between values like in `g(a b)` it changes their number
+                        let applicability =
+                            if matches!(self.lexer.peek().kind(), TokenKind::Ident(_))
+                                && *self.lexer.peek_nth(1).kind() == TokenKind::Colon
+                            {
+                                Applicability::MachineApplicable
+                            } else {
+                                Applicability::MaybeIncorrect
+                            };
+                        let mut unexpected = self.unexpected();
+                        // The separator was most likely forgotten right after the item
+                        if let (true, Some(text)) = (parsed, separator.text()) {
+                            unexpected = unexpected.suggest(Suggestion::new(
+                                format!("add a '{}'", text),
+                                TextSpan::new(end, end),
+                                text,
+                                applicability,
+                            ));
+                        }
+                        unexpected.expected_tokens(&[finisher.clone(), separator.clone()]);
+                    }
+                    self.synchronize(&stop, false);
+                    if self.lexer.peek().kind() == separator {
+                        self.lexer.eat();
+                    }
+                }
+            }
+
+            if self.lexer.peek().span().start() == start {
+                self.lexer.eat();
+            }
+        };
+        self.closers.pop();
+
+        Some((stuff, finisher_span?))
+    }
+
+    /// Whether the next token ends the file or a list enclosing the one being parsed
+    fn next_closes_enclosing(&mut self) -> bool {
+        let next = self.lexer.peek().kind();
+        let enclosing = &self.closers[..self.closers.len() - 1];
+        *next == TokenKind::EOF || enclosing.contains(next)
     }
 }
+
+/// Items are only recognized at the start of a line, as `fn` could also begin a closure
+fn is_item_start(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Function
+            | TokenKind::Import
+            | TokenKind::Type
+            | TokenKind::Struct
+            | TokenKind::Export
+    )
+}
+
+/// Whether `kind` is a closing bracket that doesn't close any of the lists being parsed
+fn is_stray_closer(kind: &TokenKind, closers: &[TokenKind]) -> bool {
+    matches!(
+        kind,
+        TokenKind::RightParen | TokenKind::RightSquare | TokenKind::RightCurly
+    ) && !closers.contains(kind)
+}
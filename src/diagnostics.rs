@@ -1,8 +1,15 @@
+use derive_getters::Getters;
 use std::borrow::Cow;
 
 use crate::lex::{Token, TokenKind};
 use crate::text::TextSpan;
 
+mod fix;
+mod render;
+mod serialize;
+
+pub use render::Renderer;
+
 #[derive(Default, Debug)]
 pub struct Diagnostics(Vec<Diagnostic>);
 
@@ -19,97 +26,467 @@ impl std::ops::DerefMut for Diagnostics {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+    Help,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+            Severity::Help => "help",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Diagnostic {
+    severity: Severity,
+    // Identifies the kind of diagnostic. Codes are never changed or reused once published
+    code: &'static str,
     message: Cow<'static, str>,
     span: TextSpan,
+    // Secondary locations, such as where something was opened or previously defined
+    labels: Vec<Label>,
+    // Extra notes and help printed after the source code, each with severity note or help
+    notes: Vec<(Severity, Cow<'static, str>)>,
+    suggestions: Vec<Suggestion>,
 }
 
-impl Diagnostics {
-    pub fn unterminated_string_literal(&mut self, position: usize) {
-        self.push(Diagnostic {
-            message: Cow::Borrowed("Unterminated string literal"),
-            span: TextSpan::single(position),
-        });
+#[derive(Debug, Getters)]
+pub struct Label {
+    span: TextSpan,
+    message: Cow<'static, str>,
+}
+
+/// How sure we are that applying a suggestion gives the code the user meant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Applicability {
+    // Safe to apply without asking, as `hel fix` does
+    MachineApplicable,
+    MaybeIncorrect,
+}
+
+impl Applicability {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Applicability::MachineApplicable => "machine-applicable",
+            Applicability::MaybeIncorrect => "maybe-incorrect",
+        }
     }
+}
+
+/// Replaces the source code covered by `span` with `replacement`
+#[derive(Debug, Getters)]
+pub struct Suggestion {
+    message: Cow<'static, str>,
+    span: TextSpan,
+    replacement: String,
+    applicability: Applicability,
+}
+
+impl Suggestion {
+    pub fn new<M: Into<Cow<'static, str>>, R: Into<String>>(
+        message: M,
+        span: TextSpan,
+        replacement: R,
+        applicability: Applicability,
+    ) -> Self {
+        Self {
+            message: message.into(),
+            span,
+            replacement: replacement.into(),
+            applicability,
+        }
+    }
+}
 
-    pub fn invalid_escape_character(&mut self, position: usize, c: char) {
-        self.push(Diagnostic {
-            message: Cow::Owned(format!("Invalid escape character: {}", c)),
-            span: TextSpan::single(position),
+impl Diagnostic {
+    pub fn error<M: Into<Cow<'static, str>>>(
+        code: &'static str,
+        message: M,
+        span: TextSpan,
+    ) -> Self {
+        Self::new(Severity::Error, code, message, span)
+    }
+
+    pub fn warning<M: Into<Cow<'static, str>>>(
+        code: &'static str,
+        message: M,
+        span: TextSpan,
+    ) -> Self {
+        Self::new(Severity::Warning, code, message, span)
+    }
+
+    fn new<M: Into<Cow<'static, str>>>(
+        severity: Severity,
+        code: &'static str,
+        message: M,
+        span: TextSpan,
+    ) -> Self {
+        Self {
+            severity,
+            code,
+            message: message.into(),
+            span,
+            labels: vec![],
+            notes: vec![],
+            suggestions: vec![],
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn span(&self) -> &TextSpan {
+        &self.span
+    }
+
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    pub fn notes(&self) -> &[(Severity, Cow<'static, str>)] {
+        &self.notes
+    }
+
+    pub fn suggestions(&self) -> &[Suggestion] {
+        &self.suggestions
+    }
+
+    /// Points at another location. A label on the diagnostic's own span explains the underline.
+    pub fn label<M: Into<Cow<'static, str>>>(mut self, span: TextSpan, message: M) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
         });
+        self
     }
 
-    pub fn unterminated_multiline_comment(&mut self, position: usize) {
-        self.push(Diagnostic {
-            message: Cow::Borrowed("Unterminated multiline comment"),
-            span: TextSpan::single(position),
-        })
+    pub fn note<M: Into<Cow<'static, str>>>(mut self, message: M) -> Self {
+        self.notes.push((Severity::Note, message.into()));
+        self
+    }
+
+    pub fn help<M: Into<Cow<'static, str>>>(mut self, message: M) -> Self {
+        self.notes.push((Severity::Help, message.into()));
+        self
+    }
+
+    pub fn suggest(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+}
+
+impl Diagnostics {
+    pub fn has_errors(&self) -> bool {
+        self.iter().any(|d| d.severity == Severity::Error)
+    }
+
+    pub fn unterminated_string_literal(&mut self, start: usize, end: usize) {
+        self.push(
+            Diagnostic::error(
+                "E0001",
+                "Unterminated string literal",
+                TextSpan::single(start),
+            )
+            .label(
+                TextSpan::new(end, end),
+                "the file ends before the string is closed",
+            ),
+        );
+    }
+
+    pub fn invalid_escape_character(&mut self, span: TextSpan, c: char) {
+        self.push(
+            Diagnostic::error("E0002", format!("Invalid escape character: {}", c), span)
+                .help(r#"the valid escape sequences are \", \', \\, \n, \t, \r, \0, \%, \xNN and \u{NNNN}"#)
+                .suggest(Suggestion::new(
+                    "escape the backslash to keep it",
+                    span,
+                    format!("\\\\{}", c),
+                    Applicability::MaybeIncorrect,
+                )),
+        );
+    }
+
+    pub fn unterminated_multiline_comment(&mut self, start: usize, end: usize) {
+        self.push(
+            Diagnostic::error(
+                "E0003",
+                "Unterminated multiline comment",
+                TextSpan::new(start, start + 2),
+            )
+            .label(
+                TextSpan::new(end, end),
+                "the file ends before the comment is closed",
+            )
+            .note("multiline comments nest, so each '#(' needs a matching ')#'"),
+        )
     }
 
     pub fn unexpected_character(&mut self, position: usize, c: char) {
-        self.push(Diagnostic {
-            message: Cow::Owned(format!("Unexpected character {}", c)),
-            span: TextSpan::single(position),
-        })
+        self.push(Diagnostic::error(
+            "E0004",
+            format!("Unexpected character {}", c),
+            TextSpan::new(position, position + c.len_utf8()),
+        ))
+    }
+
+    pub fn unterminated_char_literal(&mut self, start: usize, end: usize) {
+        self.push(
+            Diagnostic::error(
+                "E0014",
+                "Unterminated char literal",
+                TextSpan::single(start),
+            )
+            .label(
+                TextSpan::new(end, end),
+                "the line ends before the char is closed",
+            ),
+        );
     }
 
-    pub fn invalid_float_literal(&mut self, span: TextSpan) {
-        self.push(Diagnostic {
-            message: Cow::Owned(format!("Invalid float literal")),
+    /// `text` is the content between the quotes of the char literal covered by `span`
+    pub fn char_literal_length(&mut self, span: TextSpan, text: &str) {
+        let diagnostic = if text.is_empty() {
+            Diagnostic::error("E0015", "Empty char literal", span)
+        } else {
+            Diagnostic::error("E0015", "Char literal with more than one char", span).suggest(
+                Suggestion::new(
+                    "strings are written with double quotes",
+                    span,
+                    format!("\"{}\"", text),
+                    Applicability::MaybeIncorrect,
+                ),
+            )
+        };
+        self.push(diagnostic)
+    }
+
+    pub fn invalid_digit(&mut self, span: TextSpan, digit: char, base: u32) {
+        let name = match base {
+            2 => "binary",
+            8 => "octal",
+            _ => unreachable!(),
+        };
+        self.push(Diagnostic::error(
+            "E0016",
+            format!("Invalid digit {} in {} literal", digit, name),
             span,
-        })
+        ))
+    }
+
+    /// `expected` describes the digits missing from the number literal covered by `span`
+    pub fn missing_digits(&mut self, span: TextSpan, expected: &str) {
+        self.push(
+            Diagnostic::error("E0017", "Number literal without digits", span)
+                .label(span, format!("expected {}", expected)),
+        )
+    }
+
+    pub fn invalid_number_suffix(&mut self, span: TextSpan, suffix: &str, float: bool) {
+        let (kind, help) = if float {
+            ("float", "the suffixes of floats are f32 and f64")
+        } else {
+            (
+                "integer",
+                "the suffixes of integers are u8, u16, u32, u64, u128, i8, i16, i32, i64 and \
+                 i128, and decimal integers can also be f32 or f64",
+            )
+        };
+        self.push(
+            Diagnostic::error(
+                "E0018",
+                format!("Invalid suffix '{}' for {} literal", suffix, kind),
+                span,
+            )
+            .help(help),
+        )
+    }
+
+    /// `ty` is the type of the literal, either from its suffix or the largest one
+    pub fn number_out_of_range(&mut self, span: TextSpan, ty: &str) {
+        self.push(Diagnostic::error(
+            "E0019",
+            format!("Number literal out of range for {}", ty),
+            span,
+        ))
+    }
+
+    /// `expected` is what should have come next in the escape sequence covered by `span`
+    pub fn malformed_escape(&mut self, span: TextSpan, expected: &str) {
+        self.push(
+            Diagnostic::error("E0009", "Malformed escape sequence", span)
+                .label(span, format!("expected {}", expected)),
+        )
+    }
+
+    pub fn hex_escape_out_of_range(&mut self, span: TextSpan) {
+        self.push(
+            Diagnostic::error("E0010", "Hex escape out of range", span)
+                .help(r"hex escapes only go up to \x7F, other characters are written \u{NNNN}"),
+        )
     }
 
-    pub fn unexpected_token(&mut self, token: Token) -> UnexpectedToken {
-        UnexpectedToken { diagnostics: self, token }
+    /// `value` is either a surrogate or larger than the largest unicode scalar value
+    pub fn invalid_unicode_escape(&mut self, span: TextSpan, value: u32) {
+        let reason = if value <= 0x10FFFF {
+            "is a surrogate"
+        } else {
+            "is larger than 10FFFF"
+        };
+        self.push(
+            Diagnostic::error(
+                "E0011",
+                format!("Invalid unicode escape, {:X} {}", value, reason),
+                span,
+            )
+            .note("unicode escapes go up to 10FFFF, but D800 to DFFF are not characters"),
+        )
+    }
+
+    pub fn multi_line_string_start(&mut self, span: TextSpan) {
+        self.push(
+            Diagnostic::error(
+                "E0012",
+                "Text on the same line as an opening '\"\"\"'",
+                span,
+            )
+            .help("multi-line strings start on the line after the '\"\"\"'"),
+        )
+    }
+
+    pub fn insufficient_indentation(&mut self, span: TextSpan) {
+        self.push(
+            Diagnostic::error(
+                "E0013",
+                "Insufficient indentation in multi-line string",
+                span,
+            )
+            .note("the indentation of the closing '\"\"\"' is removed from every line"),
+        )
+    }
+
+    pub fn empty_interpolation(&mut self, span: TextSpan) {
+        self.push(
+            Diagnostic::error("E0020", "Empty string interpolation", span)
+                .label(span, "expected expression"),
+        )
+    }
+
+    pub fn chained_comparison(&mut self, span: TextSpan, previous: TextSpan) {
+        self.push(
+            Diagnostic::error("E0007", "Comparison operators cannot be chained", span)
+                .label(previous, "the previous comparison")
+                .help("use parentheses, or split it into two comparisons joined with 'and'"),
+        )
+    }
+
+    /// `removal` covers the '=' and the whitespace after it
+    pub fn equal_before_block_body(&mut self, span: TextSpan, removal: TextSpan) {
+        self.push(
+            Diagnostic::warning(
+                "W0001",
+                "Function bodies starting with '= {' are hella ugly",
+                span,
+            )
+            .suggest(Suggestion::new(
+                "remove the '='",
+                removal,
+                "",
+                Applicability::MachineApplicable,
+            )),
+        )
+    }
+
+    pub fn function_item_in_expression(&mut self, span: TextSpan) {
+        self.push(
+            Diagnostic::error(
+                "E0008",
+                "Functions can only be declared at the top level",
+                span,
+            )
+            .help("closures are written 'fn arg = body'"),
+        )
+    }
+
+    /// `text` is the source code of `token`, which is quoted in the message
+    pub fn unexpected_token(&mut self, token: Token, text: &str) -> UnexpectedToken<'_> {
+        let found = match token.kind() {
+            TokenKind::EOF => token.kind().to_string(),
+            // Long tokens, like some strings, are only described
+            _ if text.is_empty() || text.contains('\n') || text.chars().count() > 24 => {
+                token.kind().to_string()
+            }
+            _ => format!("'{}'", text),
+        };
+
+        UnexpectedToken {
+            diagnostics: self,
+            span: *token.span(),
+            found,
+            suggestion: None,
+        }
     }
 }
 
 #[must_use]
 pub struct UnexpectedToken<'d> {
     diagnostics: &'d mut Diagnostics,
-    token: Token,
+    span: TextSpan,
+    found: String,
+    suggestion: Option<Suggestion>,
 }
 
 impl<'d> UnexpectedToken<'d> {
+    pub fn suggest(mut self, suggestion: Suggestion) -> Self {
+        self.suggestion = Some(suggestion);
+        self
+    }
+
     pub fn expected_token(self, expected: &TokenKind) {
-        self.diagnostics.push(Diagnostic {
-            message: Cow::Owned(format!(
-                "Unexpected {:?} token, expected {:?}",
-                self.token, expected
-            )),
-            span: self.token.span().clone(),
-        })
+        self.expected(&expected.to_string())
     }
 
     pub fn expected_tokens(self, expected: &[TokenKind]) {
-        let mut message: Cow<'_, str> = Cow::Owned(format!("Unexpected {:?} token", self.token));
-        if !expected.is_empty() {
-            message.to_mut().push_str(", expected ");
-            for (i, ex) in expected.iter().enumerate() {
-                message.to_mut().push_str(&format!("{:?}", ex));
-                if i + 2 < expected.len() {
-                    message.to_mut().push_str(", ");
-                } else if i + 2 == expected.len() {
-                    message.to_mut().push_str(", or ");
-                }
-            }
-        }
+        // Sorting puts quoted tokens before descriptions like identifier
+        let mut expected: Vec<String> = expected.iter().map(TokenKind::to_string).collect();
+        expected.sort();
+        expected.dedup();
 
-        self.diagnostics.push(Diagnostic {
-            message,
-            span: self.token.span().clone(),
-        })
+        let list = match expected.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+            None => "something else".into(),
+        };
+        self.expected(&list)
     }
 
+    /// The expectation is put as a label on the token, explaining the underline
     pub fn expected(self, expected: &str) {
-        self.diagnostics.push(Diagnostic {
-            message: Cow::Owned(format!(
-                "Unexpected {:?} token, expected {}",
-                self.token, expected
-            )),
-            span: self.token.span().clone(),
-        })
+        let mut diagnostic =
+            Diagnostic::error("E0006", format!("Unexpected {}", self.found), self.span)
+                .label(self.span, format!("expected {}", expected));
+        if let Some(suggestion) = self.suggestion {
+            diagnostic = diagnostic.suggest(suggestion);
+        }
+        self.diagnostics.push(diagnostic)
     }
 }
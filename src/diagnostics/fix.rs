@@ -0,0 +1,38 @@
+use super::{Applicability, Diagnostics, Suggestion};
+use crate::text::SourceFile;
+
+impl Diagnostics {
+    /// Applies every machine-applicable suggestion for `file`, returning its fixed source code
+    /// and how many suggestions were applied. Suggestions overlapping an earlier one are skipped,
+    /// they can be applied by running again.
+    pub fn apply_fixes(&self, file: &SourceFile) -> (String, usize) {
+        let mut suggestions: Vec<&Suggestion> = self
+            .iter()
+            .flat_map(|diagnostic| diagnostic.suggestions())
+            .filter(|suggestion| *suggestion.applicability() == Applicability::MachineApplicable)
+            .filter(|suggestion| {
+                let span = suggestion.span();
+                span.start() >= file.start() && span.end() <= file.end()
+            })
+            .collect();
+        suggestions.sort_by_key(|suggestion| suggestion.span().start());
+
+        let mut applied: Vec<&Suggestion> = vec![];
+        for suggestion in suggestions {
+            match applied.last() {
+                Some(last) if last.span().end() > suggestion.span().start() => (),
+                _ => applied.push(suggestion),
+            }
+        }
+
+        let mut fixed = file.text().to_string();
+        // Going from the back keeps the offsets of the remaining suggestions valid
+        for suggestion in applied.iter().rev() {
+            let start = file.local(suggestion.span().start());
+            let end = file.local(suggestion.span().end());
+            fixed.replace_range(start..end, suggestion.replacement());
+        }
+
+        (fixed, applied.len())
+    }
+}
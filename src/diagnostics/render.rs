@@ -0,0 +1,277 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use super::{Diagnostic, Severity};
+use crate::text::{FileId, SourceMap, TextSpan};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const GREEN: &str = "\x1b[1;32m";
+const CYAN: &str = "\x1b[1;36m";
+const BLUE: &str = "\x1b[1;34m";
+
+// Spans covering more lines than this only show the first and last few of them
+const MAX_LINES: usize = 4;
+
+/// Renders diagnostics along with the lines of source code they point at. Labels pointing into
+/// other files than the diagnostic itself get their own section of the snippet.
+///
+/// ```text
+/// error[E0006]: Unexpected ')'
+///  --> main.hel:3:9
+///   |
+/// 3 | let x = )
+///   |         ^ expected expression
+/// ```
+pub struct Renderer<'a> {
+    sources: &'a SourceMap,
+    colour: bool,
+}
+
+/// A span to underline, located in the source code
+struct Annotation<'d> {
+    file: FileId,
+    start: (usize, usize),
+    end: (usize, usize),
+    primary: bool,
+    label: Option<&'d str>,
+}
+
+impl Annotation<'_> {
+    fn line_count(&self) -> usize {
+        self.end.0 - self.start.0 + 1
+    }
+
+    /// Whether `line` is shown, which is not the case for the middle lines of long spans
+    fn shows(&self, line: usize) -> bool {
+        if line < self.start.0 || line > self.end.0 {
+            return false;
+        }
+        let nth = line - self.start.0;
+        self.line_count() <= MAX_LINES
+            || nth < MAX_LINES / 2
+            || nth >= self.line_count() - MAX_LINES / 2
+    }
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(sources: &'a SourceMap, colour: bool) -> Self {
+        Self { sources, colour }
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let primary_span = *diagnostic.span();
+        let primary_label = diagnostic
+            .labels()
+            .iter()
+            .find(|label| *label.span() == primary_span);
+        let mut annotations = vec![self.annotate(
+            primary_span,
+            true,
+            primary_label.map(|label| &label.message()[..]),
+        )];
+        for label in diagnostic.labels() {
+            if *label.span() != primary_span {
+                annotations.push(self.annotate(*label.span(), false, Some(label.message())));
+            }
+        }
+
+        // The file of the diagnostic itself comes first
+        let mut files: Vec<FileId> = vec![];
+        for annotation in &annotations {
+            if !files.contains(&annotation.file) {
+                files.push(annotation.file);
+            }
+        }
+        let lines = |file: FileId| -> BTreeSet<usize> {
+            annotations
+                .iter()
+                .filter(|a| a.file == file)
+                .flat_map(|a| (a.start.0..=a.end.0).filter(move |&line| a.shows(line)))
+                .collect()
+        };
+        let last_line = files
+            .iter()
+            .filter_map(|&file| lines(file).into_iter().next_back())
+            .max()
+            .unwrap();
+        let gutter = last_line.to_string().len();
+
+        let mut out = String::new();
+        let severity = diagnostic.severity();
+        writeln!(
+            out,
+            "{}: {}",
+            self.paint(
+                severity_colour(severity),
+                &format!("{}[{}]", severity.as_str(), diagnostic.code())
+            ),
+            self.paint(BOLD, diagnostic.message())
+        )
+        .unwrap();
+        for (i, &file) in files.iter().enumerate() {
+            let source = self.sources.file(file);
+            if i > 0 {
+                self.gutter(&mut out, gutter, "");
+                out.push('\n');
+            }
+            let (line, col) = annotations.iter().find(|a| a.file == file).unwrap().start;
+            writeln!(
+                out,
+                "{}{} {}:{}:{}",
+                " ".repeat(gutter),
+                self.paint(BLUE, if i == 0 { "-->" } else { ":::" }),
+                source.name(),
+                line,
+                col
+            )
+            .unwrap();
+            self.gutter(&mut out, gutter, "");
+            out.push('\n');
+
+            let mut previous = None;
+            for line in lines(file) {
+                if matches!(previous, Some(previous) if previous + 1 < line) {
+                    writeln!(out, "{}", self.paint(BLUE, "...")).unwrap();
+                }
+                previous = Some(line);
+
+                let source_line = source
+                    .code()
+                    .nth_line(line - 1)
+                    .trim_end_matches(&['\n', '\r'][..]);
+                self.gutter(&mut out, gutter, &line.to_string());
+                writeln!(out, " {}", expand_tabs(source_line)).unwrap();
+
+                let shown = annotations
+                    .iter()
+                    .filter(|a| a.file == file && a.shows(line));
+                for annotation in shown {
+                    // Columns are 1-based, and the underline ends after the end column
+                    let from = if line == annotation.start.0 {
+                        annotation.start.1 - 1
+                    } else {
+                        0
+                    };
+                    let to = if line == annotation.end.0 {
+                        annotation.end.1
+                    } else {
+                        source_line.chars().count()
+                    };
+                    let padding = display_width(source_line.chars().take(from));
+                    let width = display_width(source_line.chars().skip(from).take(to - from));
+
+                    let (marker, colour) = if annotation.primary {
+                        ("^", severity_colour(severity))
+                    } else {
+                        ("-", BLUE)
+                    };
+                    let mut underline = marker.repeat(usize::max(width, 1));
+                    if let (Some(label), true) = (annotation.label, line == annotation.end.0) {
+                        write!(underline, " {}", label).unwrap();
+                    }
+
+                    self.gutter(&mut out, gutter, "");
+                    writeln!(
+                        out,
+                        " {}{}",
+                        " ".repeat(padding),
+                        self.paint(colour, &underline)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        let suggestions = diagnostic
+            .suggestions()
+            .iter()
+            .map(|suggestion| (Severity::Help, &suggestion.message()[..]));
+        for (severity, note) in diagnostic
+            .notes()
+            .iter()
+            .map(|(severity, note)| (*severity, &note[..]))
+            .chain(suggestions)
+        {
+            writeln!(
+                out,
+                "{} {} {}",
+                " ".repeat(gutter),
+                self.paint(BLUE, "="),
+                self.paint(BOLD, &format!("{}: {}", severity.as_str(), note))
+            )
+            .unwrap();
+        }
+
+        out
+    }
+
+    fn annotate<'d>(
+        &self,
+        span: TextSpan,
+        primary: bool,
+        label: Option<&'d str>,
+    ) -> Annotation<'d> {
+        let file = self.sources.find(span.start());
+        let source = self.sources.file(file);
+        let text = source.text();
+        // Point at the end of the last line rather than at the empty line after it
+        let clamp = |position: usize| {
+            if position >= text.len() && text.ends_with('\n') {
+                text.len() - 1
+            } else {
+                usize::min(position, text.len())
+            }
+        };
+        let (start, end) = (source.local(span.start()), source.local(span.end()));
+        // The underline ends at the start of the last char, or at the start for empty spans
+        let last = text
+            .get(..end)
+            .and_then(|text| text.chars().next_back())
+            .map_or(1, char::len_utf8);
+        let end = clamp(usize::max(end, start + last) - last);
+        let start = clamp(start);
+
+        Annotation {
+            file,
+            start: source.code().line_col(start),
+            end: source.code().line_col(usize::max(start, end)),
+            primary,
+            label,
+        }
+    }
+
+    fn gutter(&self, out: &mut String, width: usize, line: &str) {
+        let gutter = format!("{:>w$} |", line, w = width);
+        write!(out, "{}", self.paint(BLUE, &gutter)).unwrap();
+    }
+
+    fn paint(&self, colour: &str, text: &str) -> String {
+        if self.colour {
+            format!("{}{}{}", colour, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn severity_colour(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => RED,
+        Severity::Warning => YELLOW,
+        Severity::Note => GREEN,
+        Severity::Help => CYAN,
+    }
+}
+
+const TAB_WIDTH: usize = 4;
+
+fn expand_tabs(line: &str) -> String {
+    line.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+fn display_width(chars: impl Iterator<Item = char>) -> usize {
+    chars.map(|c| if c == '\t' { TAB_WIDTH } else { 1 }).sum()
+}
@@ -0,0 +1,165 @@
+use serde_json::{json, Value};
+
+use super::{Diagnostic, Diagnostics, Severity};
+use crate::text::{SourceMap, TextSpan};
+
+// Bumped whenever the JSON schema changes in a way that could break consumers
+const JSON_VERSION: usize = 1;
+
+impl Diagnostics {
+    /// Serializes the diagnostics into hel's own JSON schema:
+    ///
+    /// ```text
+    /// { "version": 1, "diagnostics": [{
+    ///     "code": "E0006", "severity": "error", "message": "..", "file": "main.hel",
+    ///     "range": { "start": 10, "end": 11 },
+    ///     "start": { "line": 2, "column": 3 }, "end": { "line": 2, "column": 4 },
+    ///     "labels": [{ "message": "..", "range": .., "start": .., "end": .. }],
+    ///     "notes": [{ "severity": "help", "message": ".." }],
+    ///     "suggestions": [{
+    ///         "message": "..", "replacement": ",", "applicability": "machine-applicable",
+    ///         "range": .., "start": .., "end": ..
+    ///     }]
+    /// }] }
+    /// ```
+    ///
+    /// Labels and suggestions have a `file` as well, since they can point into other files than
+    /// the diagnostic. Ranges are byte offsets into that file, lines and columns start at 1,
+    /// columns count chars, and the end of both ranges is exclusive.
+    pub fn to_json(&self, sources: &SourceMap) -> Value {
+        let diagnostics = self
+            .iter()
+            .map(|diagnostic| {
+                let mut value = json!({
+                    "code": diagnostic.code(),
+                    "severity": diagnostic.severity().as_str(),
+                    "message": diagnostic.message(),
+                    "labels": diagnostic.labels().iter().map(|label| {
+                        let mut value = json!({ "message": label.message() });
+                        locate(&mut value, *label.span(), sources);
+                        value
+                    }).collect::<Vec<Value>>(),
+                    "notes": diagnostic.notes().iter().map(|(severity, message)| json!({
+                        "severity": severity.as_str(),
+                        "message": message,
+                    })).collect::<Vec<Value>>(),
+                    "suggestions": diagnostic.suggestions().iter().map(|suggestion| {
+                        let mut value = json!({
+                            "message": suggestion.message(),
+                            "replacement": suggestion.replacement(),
+                            "applicability": suggestion.applicability().as_str(),
+                        });
+                        locate(&mut value, *suggestion.span(), sources);
+                        value
+                    }).collect::<Vec<Value>>(),
+                });
+                locate(&mut value, *diagnostic.span(), sources);
+                value
+            })
+            .collect::<Vec<Value>>();
+
+        json!({
+            "version": JSON_VERSION,
+            "diagnostics": diagnostics,
+        })
+    }
+
+    /// Serializes the diagnostics into a SARIF 2.1.0 log with a single run
+    pub fn to_sarif(&self, sources: &SourceMap) -> Value {
+        let mut rules: Vec<&str> = self.iter().map(Diagnostic::code).collect();
+        rules.sort_unstable();
+        rules.dedup();
+
+        let results = self
+            .iter()
+            .map(|diagnostic| {
+                // SARIF has no notes, so they are put after the message like when rendered
+                let mut message = diagnostic.message().to_string();
+                for (severity, note) in diagnostic.notes() {
+                    message.push_str(&format!("\n{}: {}", severity.as_str(), note));
+                }
+
+                json!({
+                    "ruleId": diagnostic.code(),
+                    "ruleIndex": rules.binary_search(&diagnostic.code()).unwrap(),
+                    "level": match diagnostic.severity() {
+                        Severity::Error => "error",
+                        Severity::Warning => "warning",
+                        Severity::Note | Severity::Help => "note",
+                    },
+                    "message": { "text": message },
+                    "locations": [sarif_location(*diagnostic.span(), sources)],
+                    "relatedLocations": diagnostic.labels().iter().enumerate().map(|(i, label)| {
+                        let mut location = sarif_location(*label.span(), sources);
+                        location["id"] = json!(i);
+                        location["message"] = json!({ "text": label.message() });
+                        location
+                    }).collect::<Vec<Value>>(),
+                    "fixes": diagnostic.suggestions().iter().map(|suggestion| {
+                        let location = sarif_location(*suggestion.span(), sources);
+                        let physical = &location["physicalLocation"];
+                        json!({
+                            "description": { "text": suggestion.message() },
+                            "artifactChanges": [{
+                                "artifactLocation": physical["artifactLocation"],
+                                "replacements": [{
+                                    "deletedRegion": physical["region"],
+                                    "insertedContent": { "text": suggestion.replacement() },
+                                }],
+                            }],
+                        })
+                    }).collect::<Vec<Value>>(),
+                })
+            })
+            .collect::<Vec<Value>>();
+
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "hel",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules
+                            .iter()
+                            .map(|code| json!({ "id": code }))
+                            .collect::<Vec<Value>>(),
+                    },
+                },
+                "columnKind": "utf16CodeUnits",
+                "results": results,
+            }],
+        })
+    }
+}
+
+/// Adds the `file`, `range`, `start` and `end` of `span` to a JSON object
+fn locate(value: &mut Value, span: TextSpan, sources: &SourceMap) {
+    let file = sources.file_of(span);
+    let (start, end) = (file.local(span.start()), file.local(span.end()));
+    let (start_line, start_column) = file.code().line_col(start);
+    let (end_line, end_column) = file.code().line_col(end);
+    value["file"] = json!(file.name());
+    value["range"] = json!({ "start": start, "end": end });
+    value["start"] = json!({ "line": start_line, "column": start_column });
+    value["end"] = json!({ "line": end_line, "column": end_column });
+}
+
+// Columns count UTF-16 code units, as declared by the run's `columnKind`
+fn sarif_location(span: TextSpan, sources: &SourceMap) -> Value {
+    let file = sources.file_of(span);
+    let (start_line, start_column) = file.code().line_col_utf16(file.local(span.start()));
+    let (end_line, end_column) = file.code().line_col_utf16(file.local(span.end()));
+    json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": file.name() },
+            "region": {
+                "startLine": start_line,
+                "startColumn": start_column,
+                "endLine": end_line,
+                "endColumn": end_column,
+            },
+        },
+    })
+}
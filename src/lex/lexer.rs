@@ -1,52 +1,118 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::iter::Peekable;
+use std::mem;
 use std::str::Chars;
 
-use super::{Token, TokenKind};
+use super::{FloatSuffix, IntSuffix, StringPart, Token, TokenKind, Trivia, TriviaKind};
 use crate::diagnostics::Diagnostics;
-use crate::text::TextSpan;
+use crate::symbol::Symbol;
+use crate::text::{SourceFile, TextSpan};
 
 #[derive(Debug)]
 pub struct Lexer<'a> {
+    source: &'a str,
+    // Where the source starts, which all positions are offset by
+    start: usize,
     input: Peekable<Chars<'a>>,
     position: usize,
     diagnostics: Diagnostics,
+    // How many diagnostics were taken out of `diagnostics` so far
+    taken: usize,
     keywords: HashMap<&'static str, TokenKind>,
-    peeked: Option<Token>,
+    // Tokens lexed ahead of time by `peek_nth`, in order
+    peeked: VecDeque<Token>,
+    previous_end: usize,
+    // The trivia since the last token, or None if trivia is not kept
+    trivia: Option<Vec<Trivia>>,
+    // The lines of the doc comments since the last token
+    doc: Option<String>,
     just_saw_whitespace: bool,
+    just_saw_newline: bool,
+}
+
+/// The state of a `Lexer` at some point, see `Lexer::checkpoint`
+#[derive(Debug, Clone)]
+pub struct Checkpoint<'a> {
+    input: Peekable<Chars<'a>>,
+    position: usize,
+    // How many diagnostics had been reported, including those taken since
+    diagnostics: usize,
+    peeked: VecDeque<Token>,
+    previous_end: usize,
+    trivia: Option<Vec<Trivia>>,
+    doc: Option<String>,
+    just_saw_whitespace: bool,
+    just_saw_newline: bool,
+}
+
+// Iterates over the tokens up to, but not including, the EOF token, which is still there to
+// peek at or eat afterwards
+impl Iterator for Lexer<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        match self.peek().kind() {
+            TokenKind::EOF => None,
+            _ => Some(self.eat()),
+        }
+    }
 }
 
 impl<'a> From<&'a String> for Lexer<'a> {
     fn from(input: &'a String) -> Self {
-        Self {
-            input: input.chars().peekable(),
-            position: 0,
-            diagnostics: Diagnostics::default(),
-            keywords: Self::get_keywords(),
-            peeked: None,
-            just_saw_whitespace: false,
-        }
+        Self::new(input, 0)
     }
 }
 
 impl<'a> From<&'a str> for Lexer<'a> {
     fn from(input: &'a str) -> Self {
+        Self::new(input, 0)
+    }
+}
+
+// Positions of tokens lexed from a file in a source map are where they are in the source map
+impl<'a> From<&'a SourceFile> for Lexer<'a> {
+    fn from(file: &'a SourceFile) -> Self {
+        Self::new(file.text(), file.start())
+    }
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str, start: usize) -> Self {
         Self {
+            source: input,
+            start,
             input: input.chars().peekable(),
-            position: 0,
+            position: start,
             diagnostics: Diagnostics::default(),
+            taken: 0,
             keywords: Self::get_keywords(),
-            peeked: None,
+            peeked: VecDeque::new(),
+            previous_end: start,
+            trivia: None,
+            doc: None,
             just_saw_whitespace: false,
+            just_saw_newline: false,
         }
     }
-}
 
-impl<'a> Lexer<'a> {
+    /// Keeps the whitespace and comments between tokens as the tokens' leading trivia, so that
+    /// every byte of the source code is covered by either a token or a piece of trivia. The
+    /// trivia at the end of the file belongs to the EOF token.
+    pub fn with_trivia(mut self) -> Self {
+        self.trivia = Some(vec![]);
+        self
+    }
+
     pub fn diagnostics(&self) -> &Diagnostics {
         &self.diagnostics
     }
 
+    pub fn take_diagnostics(&mut self) -> Diagnostics {
+        self.taken += self.diagnostics.len();
+        mem::take(&mut self.diagnostics)
+    }
+
     #[rustfmt::skip]
     fn get_keywords() -> HashMap<&'static str, TokenKind> {
         [
@@ -59,15 +125,21 @@ impl<'a> Lexer<'a> {
             ("fn",     TokenKind::Function),
             ("type",   TokenKind::Type),
             ("struct", TokenKind::Struct),
+            ("enum",   TokenKind::Enum),
+            ("match",  TokenKind::Match),
             ("if",     TokenKind::If),
             ("then",   TokenKind::Then),
             ("else",   TokenKind::Else),
             ("for",    TokenKind::For),
             ("in",     TokenKind::In),
+            ("while",  TokenKind::While),
             ("loop",   TokenKind::Loop),
             ("return", TokenKind::Return),
+            ("yield",  TokenKind::Yield),
+            ("break",  TokenKind::Break),
             ("defer",  TokenKind::Defer),
             ("import", TokenKind::Import),
+            ("export", TokenKind::Export),
         ].iter().cloned().collect()
     }
 
@@ -76,43 +148,436 @@ impl<'a> Lexer<'a> {
     }
 
     fn eat_char(&mut self) -> char {
-        self.position += 1;
-        self.input.next().unwrap_or('\0')
+        let c = self.input.next();
+        // Positions are byte offsets, so chars outside of ASCII move it further than one
+        self.position += c.map_or(0, char::len_utf8);
+        c.unwrap_or('\0')
     }
 
     fn peek_char(&mut self) -> char {
         self.input.peek().copied().unwrap_or('\0')
     }
 
-    fn lex_string(&mut self) -> TokenKind {
+    /// Lexes the rest of a string after its opening quotes, which are three for multi-line
+    /// strings. Their content starts on the next line, and the indentation of the closing quotes
+    /// is removed from every line.
+    fn lex_string(&mut self, start: usize, multi_line: bool) -> TokenKind {
         let mut value = String::new();
+        let mut parts = vec![];
+        let mut interpolated = false;
+
+        let indentation = if multi_line {
+            let indentation = self.closing_indentation();
+            let text_start = self.position;
+            while !self.is_eof() && self.peek_char() != '\n' {
+                self.eat_char();
+            }
+            if self.rest_of_line_is_text(text_start) {
+                self.diagnostics
+                    .multi_line_string_start(TextSpan::new(text_start, self.position));
+            }
+            self.eat_char();
+            self.strip_indentation(indentation);
+            indentation
+        } else {
+            0
+        };
+
         loop {
             if self.is_eof() {
-                self.diagnostics.unterminated_string_literal(self.position);
+                self.diagnostics
+                    .unterminated_string_literal(start, self.position);
                 break;
             }
             match self.eat_char() {
-                '"' => {
+                '"' if !multi_line => {
                     break;
                 }
-                '\\' => match self.eat_char() {
-                    '"' => value.push('"'),
-                    '\\' => value.push('\\'),
-                    'n' => value.push('\n'),
-                    't' => value.push('\t'),
-                    'r' => value.push('\r'),
-                    c => {
-                        self.diagnostics.invalid_escape_character(self.position, c);
+                '"' if self.rest().starts_with("\"\"") => {
+                    self.eat_char();
+                    self.eat_char();
+                    // The newline before the closing quotes is not part of the string
+                    if value.ends_with('\n') {
+                        value.pop();
+                        if value.ends_with('\r') {
+                            value.pop();
+                        }
                     }
-                },
+                    break;
+                }
+                '\n' if multi_line => {
+                    value.push('\n');
+                    self.strip_indentation(indentation);
+                }
+                '\\' => self.lex_escape(&mut value),
+                '%' if self.peek_char() == '(' => {
+                    self.eat_char();
+                    interpolated = true;
+                    if !value.is_empty() {
+                        parts.push(StringPart::Literal(mem::take(&mut value)));
+                    }
+                    // If it is unterminated, so is the string, which is reported above
+                    if let Some(span) = self.skip_interpolation() {
+                        parts.push(StringPart::Interpolation(span));
+                    }
+                }
                 c => {
                     value.push(c);
                 }
             }
         }
+
+        if !interpolated {
+            return TokenKind::String(value);
+        }
+        if !value.is_empty() {
+            parts.push(StringPart::Literal(value));
+        }
+        TokenKind::InterpolatedString(parts)
+    }
+
+    /// Whether there is more than whitespace between `start` and the current position
+    fn rest_of_line_is_text(&self, start: usize) -> bool {
+        !self
+            .source_text(TextSpan::new(start, self.position))
+            .trim()
+            .is_empty()
+    }
+
+    /// The indentation of the line with the closing quotes of a multi-line string
+    fn closing_indentation(&self) -> usize {
+        self.rest()
+            .lines()
+            .skip(1)
+            .find_map(|line| {
+                let content = line.trim_start_matches(&[' ', '\t'][..]);
+                if content.starts_with("\"\"\"") {
+                    Some(line.len() - content.len())
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(0)
+    }
+
+    /// Skips the indentation at the start of a line in a multi-line string. Blank lines may be
+    /// indented less than the rest.
+    fn strip_indentation(&mut self, indentation: usize) {
+        let start = self.position;
+        for _ in 0..indentation {
+            match self.peek_char() {
+                ' ' | '\t' => {
+                    self.eat_char();
+                }
+                '\r' | '\n' | '\0' => break,
+                _ => {
+                    self.diagnostics
+                        .insufficient_indentation(TextSpan::new(start, self.position));
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Lexes a number literal after its first digit. Digits can be separated by `_`, and
+    /// integers can be written in binary, octal or hex with a `0b`, `0o` or `0x` prefix.
+    fn lex_number(&mut self, start: usize, first: char) -> TokenKind {
+        let base = match (first, self.peek_char()) {
+            ('0', 'b') => 2,
+            ('0', 'o') => 8,
+            ('0', 'x') => 16,
+            _ => 10,
+        };
+        let mut digits = String::new();
+        if base == 10 {
+            digits.push(first);
+        } else {
+            self.eat_char();
+        }
+        let reported = self.diagnostics.len();
+        self.eat_digits(&mut digits, base);
+        // Invalid digits have been reported already
+        if digits.is_empty() && self.diagnostics.len() == reported {
+            let span = TextSpan::new(start, self.position);
+            let prefix = self.source_text(span);
+            self.diagnostics
+                .missing_digits(span, &format!("digits after '{}'", prefix));
+        }
+
+        // A dot only starts the fraction if a digit follows, as in neither `1..2` nor `1.abs()`
+        let mut float = false;
+        let next_is_digit =
+            |lexer: &Self, nth| matches!(lexer.rest().chars().nth(nth), Some('0'..='9'));
+        if base == 10 && self.peek_char() == '.' && next_is_digit(self, 1) {
+            float = true;
+            digits.push(self.eat_char());
+            self.eat_digits(&mut digits, 10);
+        }
+        if base == 10 && matches!(self.peek_char(), 'e' | 'E') {
+            float = true;
+            digits.push(self.eat_char());
+            if matches!(self.peek_char(), '+' | '-') {
+                digits.push(self.eat_char());
+            }
+            let exponent_start = digits.len();
+            self.eat_digits(&mut digits, 10);
+            if digits.len() == exponent_start {
+                let span = TextSpan::new(start, self.position);
+                self.diagnostics
+                    .missing_digits(span, "digits in the exponent");
+            }
+        }
+
+        let suffix_start = self.position;
+        let mut suffix = String::new();
+        while self.peek_char() == '_' || self.peek_char().is_ascii_alphanumeric() {
+            suffix.push(self.eat_char());
+        }
+        let span = TextSpan::new(start, self.position);
+        let suffix_span = TextSpan::new(suffix_start, self.position);
+
+        if let Some(float_suffix) = FloatSuffix::parse(&suffix) {
+            if base == 10 {
+                return self.float_literal(span, &digits, Some(float_suffix));
+            }
+        }
+        if float {
+            if !suffix.is_empty() {
+                self.diagnostics
+                    .invalid_number_suffix(suffix_span, &suffix, true);
+            }
+            return self.float_literal(span, &digits, None);
+        }
+
+        let int_suffix = IntSuffix::parse(&suffix);
+        if int_suffix.is_none() && !suffix.is_empty() {
+            self.diagnostics
+                .invalid_number_suffix(suffix_span, &suffix, false);
+        }
+        let (ty, max) = int_suffix.map_or(("u128", u128::MAX), |s| (s.as_str(), s.max()));
+        match u128::from_str_radix(&digits, base) {
+            Ok(value) if value <= max => TokenKind::Integer(value, int_suffix),
+            Ok(_) | Err(_) if !digits.is_empty() => {
+                self.diagnostics.number_out_of_range(span, ty);
+                TokenKind::Integer(0, int_suffix)
+            }
+            _ => TokenKind::Integer(0, int_suffix),
+        }
+    }
+
+    /// Eats digits and `_` separators, pushing the digits to `digits`. Digits too large for
+    /// `base` are reported and left out.
+    fn eat_digits(&mut self, digits: &mut String, base: u32) {
+        loop {
+            let c = self.peek_char();
+            if c == '_' {
+                self.eat_char();
+            } else if c.is_digit(base) {
+                digits.push(self.eat_char());
+            } else if base < 10 && c.is_ascii_digit() {
+                let span = TextSpan::single(self.position);
+                self.diagnostics.invalid_digit(span, c, base);
+                self.eat_char();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn float_literal(
+        &mut self,
+        span: TextSpan,
+        digits: &str,
+        suffix: Option<FloatSuffix>,
+    ) -> TokenKind {
+        let value = match suffix {
+            Some(FloatSuffix::F32) => digits.parse::<f32>().map(f64::from),
+            _ => digits.parse::<f64>(),
+        };
+        match value {
+            Ok(value) if value.is_finite() => TokenKind::Float(value, suffix),
+            Ok(_) => {
+                let ty = suffix.map_or("f64", |suffix| suffix.as_str());
+                self.diagnostics.number_out_of_range(span, ty);
+                TokenKind::Float(0.0, suffix)
+            }
+            Err(_) => {
+                // The missing digits have already been reported
+                TokenKind::Float(0.0, suffix)
+            }
+        }
+    }
+
+    /// Lexes a char literal after its opening quote, with the same escape sequences as strings
+    fn lex_char(&mut self, start: usize) -> TokenKind {
+        let reported = self.diagnostics.len();
+        let mut value = String::new();
+        loop {
+            // Char literals are short, so a missing quote is caught at the end of the line
+            if self.is_eof() || self.peek_char() == '\n' {
+                self.diagnostics
+                    .unterminated_char_literal(start, self.position);
+                break;
+            }
+            match self.eat_char() {
+                '\'' => break,
+                '\\' => self.lex_escape(&mut value),
+                c => value.push(c),
+            }
+        }
+
+        let mut chars = value.chars();
+        let c = chars.next();
+        if self.diagnostics.len() == reported && (c.is_none() || chars.next().is_some()) {
+            let span = TextSpan::new(start, self.position);
+            let text = self.source_text(TextSpan::new(start + 1, self.position - 1));
+            self.diagnostics.char_literal_length(span, text);
+        }
+        TokenKind::Char(c.unwrap_or('\0'))
+    }
+
+    /// Lexes a raw string after its `r`, which is ended by a quote followed by as many `#` as
+    /// it started with. Escape sequences and interpolations are kept as they are.
+    fn lex_raw_string(&mut self, start: usize) -> TokenKind {
+        let mut hashes = 0;
+        while self.peek_char() == '#' {
+            self.eat_char();
+            hashes += 1;
+        }
+        self.eat_char(); // "
+
+        let closing = format!("\"{}", "#".repeat(hashes));
+        let mut value = String::new();
+        loop {
+            if self.rest().starts_with(&closing) {
+                for _ in 0..closing.len() {
+                    self.eat_char();
+                }
+                break;
+            }
+            if self.is_eof() {
+                self.diagnostics
+                    .unterminated_string_literal(start, self.position);
+                break;
+            }
+            value.push(self.eat_char());
+        }
         TokenKind::String(value)
     }
 
+    /// Lexes an escape sequence after its backslash, pushing the char it stands for to `value`
+    fn lex_escape(&mut self, value: &mut String) {
+        let start = self.position - 1;
+        // The string is unterminated, which is reported by the caller
+        if self.is_eof() {
+            return;
+        }
+        let c = match self.eat_char() {
+            '"' => '"',
+            '\'' => '\'',
+            '\\' => '\\',
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '%' => '%',
+            'x' => {
+                let digits = self.eat_hex_digits(2);
+                let span = TextSpan::new(start, self.position);
+                if digits.len() < 2 {
+                    self.diagnostics
+                        .malformed_escape(span, "two hex digits after '\\x'");
+                    return;
+                }
+                let value = u8::from_str_radix(&digits, 16).unwrap();
+                if value > 0x7F {
+                    self.diagnostics.hex_escape_out_of_range(span);
+                    return;
+                }
+                value as char
+            }
+            'u' => {
+                if self.peek_char() != '{' {
+                    let span = TextSpan::new(start, self.position);
+                    self.diagnostics.malformed_escape(span, "'{' after '\\u'");
+                    return;
+                }
+                self.eat_char();
+                let digits = self.eat_hex_digits(6);
+                if digits.is_empty() || self.peek_char() != '}' {
+                    let span = TextSpan::new(start, self.position);
+                    let expected = if digits.is_empty() {
+                        "1 to 6 hex digits"
+                    } else {
+                        "'}'"
+                    };
+                    self.diagnostics.malformed_escape(span, expected);
+                    return;
+                }
+                self.eat_char();
+                let span = TextSpan::new(start, self.position);
+                let value = u32::from_str_radix(&digits, 16).unwrap();
+                match std::char::from_u32(value) {
+                    Some(c) => c,
+                    None => {
+                        self.diagnostics.invalid_unicode_escape(span, value);
+                        return;
+                    }
+                }
+            }
+            c => {
+                let span = TextSpan::new(start, self.position);
+                self.diagnostics.invalid_escape_character(span, c);
+                return;
+            }
+        };
+        value.push(c);
+    }
+
+    fn eat_hex_digits(&mut self, max: usize) -> String {
+        let mut digits = String::new();
+        while digits.len() < max && self.peek_char().is_ascii_hexdigit() {
+            digits.push(self.eat_char());
+        }
+        digits
+    }
+
+    /// The source code after the current position
+    fn rest(&self) -> &'a str {
+        &self.source[self.position - self.start..]
+    }
+
+    /// Lexes past the expression of an interpolation and its closing paren, returning the span
+    /// of the expression. Strings and parentheses inside of it are handled like anywhere else.
+    fn skip_interpolation(&mut self) -> Option<TextSpan> {
+        // The tokens are lexed again when parsing the expression, so they leave no trace here
+        let trivia = self.trivia.take();
+        let doc = self.doc.take();
+        let whitespace = (self.just_saw_whitespace, self.just_saw_newline);
+
+        let start = self.position;
+        let mut depth = 0;
+        let end = loop {
+            let token = match self.process() {
+                Some(token) => token,
+                None => continue,
+            };
+            match token.kind() {
+                TokenKind::LeftParen => depth += 1,
+                TokenKind::RightParen if depth == 0 => break Some(token.span().start()),
+                TokenKind::RightParen => depth -= 1,
+                TokenKind::EOF => break None,
+                _ => (),
+            }
+        };
+
+        self.trivia = trivia;
+        self.doc = doc;
+        self.just_saw_whitespace = whitespace.0;
+        self.just_saw_newline = whitespace.1;
+        end.map(|end| TextSpan::new(start, end))
+    }
+
     fn process(&mut self) -> Option<Token> {
         if self.is_eof() {
             return Some(Token::new(
@@ -120,6 +585,7 @@ impl<'a> Lexer<'a> {
                 TextSpan::new(self.position, self.position),
                 self.just_saw_whitespace,
                 false,
+                self.just_saw_newline,
             ));
         }
 
@@ -136,6 +602,7 @@ impl<'a> Lexer<'a> {
             '?' => TokenKind::Quest,
             '@' => TokenKind::At,
             '$' => TokenKind::Dollar,
+            ';' => TokenKind::Semicolon,
             '.' => match self.peek_char() {
                 '.' => {
                     self.eat_char();
@@ -236,6 +703,10 @@ impl<'a> Lexer<'a> {
                     self.eat_char();
                     TokenKind::EqualEqual
                 }
+                '>' => {
+                    self.eat_char();
+                    TokenKind::FatArrow
+                }
                 _ => TokenKind::Equal,
             },
             '<' => match self.peek_char() {
@@ -253,114 +724,91 @@ impl<'a> Lexer<'a> {
                 _ => TokenKind::Greater,
             },
             '#' => match self.peek_char() {
-                '-' => {
+                '(' => {
+                    self.eat_char(); // (
                     let mut depth = 1;
-                    loop {
+                    while depth > 0 {
                         if self.is_eof() {
                             self.diagnostics
-                                .unterminated_multiline_comment(self.position);
+                                .unterminated_multiline_comment(start, self.position);
                             break;
                         }
-                        let a = self.eat_char();
-                        let b = self.peek_char();
-
-                        if (a, b) == ('#', '-') {
+                        if self.rest().starts_with("#(") {
                             depth += 1;
-                        } else if (a, b) == ('-', '#') {
+                            self.eat_char();
+                        } else if self.rest().starts_with(")#") {
                             depth -= 1;
-                        }
-                        if depth == 0 {
                             self.eat_char();
-                            break;
                         }
-                    }
-                    return None;
-                }
-                _ => {
-                    while self.peek_char() != '\n' {
                         self.eat_char();
                     }
+                    self.push_trivia(TriviaKind::MultilineComment, start);
                     return None;
                 }
-            },
-            '"' => self.lex_string(),
-            c @ '0'..='9' => {
-                let base = match self.peek_char() {
-                    'b' => {
+                '#' => {
+                    self.eat_char(); // #
+                    let text_start = self.position;
+                    while !self.is_eof() && self.peek_char() != '\n' {
                         self.eat_char();
-                        2
                     }
-                    'x' => {
-                        self.eat_char();
-                        16
-                    }
-                    _ => 10,
-                };
-                let get_digit_value = |d| {
-                    "0123456789abcdef"
-                        .bytes()
-                        .take(base)
-                        .enumerate()
-                        .find(|(_, digit)| *digit as char == d)
-                        .map(|(i, _)| i)
-                };
-                let mut value = get_digit_value(c).unwrap();
-                while let Some(val) = get_digit_value(self.peek_char()) {
-                    self.eat_char();
-                    value *= base;
-                    value += val;
-                }
-
-                if self.peek_char() == '.' || self.peek_char() == 'e' {
-                    let mut s = value.to_string();
-                    if self.peek_char() == '.' {
-                        s.push(self.eat_char());
-                        while self.peek_char().is_ascii_digit() {
-                            s.push(self.eat_char());
-                        }
-                    }
-                    if self.peek_char() == 'e' {
-                        s.push(self.eat_char());
-                        while self.peek_char().is_ascii_digit() {
-                            s.push(self.eat_char());
+                    let text = self.source_text(TextSpan::new(text_start, self.position));
+                    let text = text.trim_end_matches('\r');
+                    let text = text.strip_prefix(' ').unwrap_or(text);
+                    match &mut self.doc {
+                        Some(doc) => {
+                            doc.push('\n');
+                            doc.push_str(text);
                         }
+                        None => self.doc = Some(text.to_string()),
                     }
-                    match s.parse() {
-                        Ok(value) => TokenKind::Float(value),
-                        Err(_err) => {
-                            self.diagnostics
-                                .invalid_float_literal(TextSpan::new(start, self.position));
-                            self.just_saw_whitespace = false;
-                            return None;
-                        }
+                    self.push_trivia(TriviaKind::DocComment, start);
+                    return None;
+                }
+                _ => {
+                    while !self.is_eof() && self.peek_char() != '\n' {
+                        self.eat_char();
                     }
-                } else {
-                    TokenKind::Integer(value)
+                    self.push_trivia(TriviaKind::Comment, start);
+                    return None;
                 }
+            },
+            '"' if self.rest().starts_with("\"\"") => {
+                self.eat_char();
+                self.eat_char();
+                self.lex_string(start, true)
+            }
+            '"' => self.lex_string(start, false),
+            '\'' => self.lex_char(start),
+            'r' if self.rest().trim_start_matches('#').starts_with('"') => {
+                self.lex_raw_string(start)
             }
+            c @ '0'..='9' => self.lex_number(start, c),
             w if w == '_' || w.is_alphabetic() => {
-                let mut value = String::new();
-                value.push(w);
                 while self.peek_char() == '_' || self.peek_char().is_alphanumeric() {
-                    value.push(self.eat_char());
+                    self.eat_char();
                 }
 
-                if let Some(keyword) = self.keywords.get(value.as_str()) {
+                let value = self.source_text(TextSpan::new(start, self.position));
+                if let Some(keyword) = self.keywords.get(value) {
                     keyword.clone()
                 } else {
-                    TokenKind::Ident(value)
+                    TokenKind::Ident(Symbol::intern(value))
                 }
             }
             s if s.is_whitespace() => {
+                let mut newline = s == '\n';
                 while self.peek_char().is_whitespace() {
-                    self.eat_char();
+                    newline |= self.eat_char() == '\n';
                 }
 
                 self.just_saw_whitespace = true;
+                self.just_saw_newline |= newline;
+                self.push_trivia(TriviaKind::Whitespace, start);
                 return None;
             }
             c => {
-                self.diagnostics.unexpected_character(self.position, c);
+                self.diagnostics.unexpected_character(start, c);
+                self.push_trivia(TriviaKind::Skipped, start);
 
                 self.just_saw_whitespace = false;
                 return None;
@@ -369,34 +817,109 @@ impl<'a> Lexer<'a> {
 
         let whitespace_before = self.just_saw_whitespace;
         let whitespace_after = self.peek_char().is_whitespace();
+        let newline_before = self.just_saw_newline;
 
         self.just_saw_whitespace = false;
+        self.just_saw_newline = false;
 
         return Some(Token::new(
             kind,
             TextSpan::new(start, self.position),
             whitespace_before,
             whitespace_after,
+            newline_before,
         ));
     }
 
+    /// The source code covered by `span`
+    pub fn source_text(&self, span: TextSpan) -> &'a str {
+        let byte = |position: usize| usize::min(position - self.start, self.source.len());
+        &self.source[byte(span.start())..byte(span.end())]
+    }
+
+    /// A lexer for the source code covered by `span`, whose tokens get the same positions as if
+    /// they were lexed by this lexer
+    pub fn sub_lexer(&self, span: TextSpan) -> Lexer<'a> {
+        Lexer::new(self.source_text(span), span.start())
+    }
+
+    /// Where the last eaten token ended
+    pub fn previous_end(&self) -> usize {
+        self.previous_end
+    }
+
     pub fn peek(&mut self) -> &Token {
-        if self.peeked.is_none() {
-            self.peeked = Some(self.eat());
+        self.peek_nth(0)
+    }
+
+    /// The token `n` tokens ahead of the next one, so `peek_nth(0)` is the same as `peek()`.
+    /// Peeking past the end of the file gives the EOF token.
+    pub fn peek_nth(&mut self, n: usize) -> &Token {
+        while self.peeked.len() <= n {
+            let token = self.next_token();
+            self.peeked.push_back(token);
         }
 
-        self.peeked.as_ref().unwrap()
+        &self.peeked[n]
     }
 
     pub fn eat(&mut self) -> Token {
-        if let Some(token) = self.peeked.take() {
-            return token;
+        let token = match self.peeked.pop_front() {
+            Some(token) => token,
+            None => self.next_token(),
+        };
+        self.previous_end = token.span().end();
+
+        token
+    }
+
+    /// Saves the state of the lexer, to go back to with `rewind`
+    pub fn checkpoint(&self) -> Checkpoint<'a> {
+        Checkpoint {
+            input: self.input.clone(),
+            position: self.position,
+            diagnostics: self.taken + self.diagnostics.len(),
+            peeked: self.peeked.clone(),
+            previous_end: self.previous_end,
+            trivia: self.trivia.clone(),
+            doc: self.doc.clone(),
+            just_saw_whitespace: self.just_saw_whitespace,
+            just_saw_newline: self.just_saw_newline,
         }
+    }
 
-        loop {
+    /// Goes back to `checkpoint`, which must have been made by this lexer. Tokens eaten since
+    /// then are lexed again, and diagnostics reported since then are dropped, except for those
+    /// that were already taken.
+    pub fn rewind(&mut self, checkpoint: Checkpoint<'a>) {
+        self.input = checkpoint.input;
+        self.position = checkpoint.position;
+        self.diagnostics
+            .truncate(checkpoint.diagnostics.saturating_sub(self.taken));
+        self.peeked = checkpoint.peeked;
+        self.previous_end = checkpoint.previous_end;
+        self.trivia = checkpoint.trivia;
+        self.doc = checkpoint.doc;
+        self.just_saw_whitespace = checkpoint.just_saw_whitespace;
+        self.just_saw_newline = checkpoint.just_saw_newline;
+    }
+
+    fn next_token(&mut self) -> Token {
+        let token = loop {
             if let Some(token) = self.process() {
                 break token;
             }
+        };
+        let token = token.with_doc(self.doc.take());
+        match &mut self.trivia {
+            Some(trivia) => token.with_leading_trivia(mem::take(trivia)),
+            None => token,
+        }
+    }
+
+    fn push_trivia(&mut self, kind: TriviaKind, start: usize) {
+        if let Some(trivia) = &mut self.trivia {
+            trivia.push(Trivia::new(kind, TextSpan::new(start, self.position)));
         }
     }
 }
@@ -1,5 +1,5 @@
 mod lexer;
 mod token;
 
-pub use lexer::Lexer;
-pub use token::{Token, TokenKind};
+pub use lexer::{Checkpoint, Lexer};
+pub use token::{FloatSuffix, IntSuffix, StringPart, Token, TokenKind, Trivia, TriviaKind};
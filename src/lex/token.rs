@@ -1,6 +1,7 @@
 use derive_getters::Getters;
-use std::mem;
+use std::{fmt, mem};
 
+use crate::symbol::Symbol;
 use crate::text::TextSpan;
 
 #[derive(Debug, Clone, Getters)]
@@ -9,6 +10,11 @@ pub struct Token {
     span: TextSpan,
     whitespace_before: bool,
     whitespace_after: bool,
+    newline_before: bool,
+    // Only filled in when lexing with trivia, see `Lexer::with_trivia`
+    leading_trivia: Vec<Trivia>,
+    // The `##` doc comments right before the token, without the `##`
+    doc: Option<String>,
 }
 
 impl Token {
@@ -17,21 +23,66 @@ impl Token {
         span: TextSpan,
         whitespace_before: bool,
         whitespace_after: bool,
+        newline_before: bool,
     ) -> Self {
         Self {
             kind,
             span,
             whitespace_before,
             whitespace_after,
+            newline_before,
+            leading_trivia: Vec::new(),
+            doc: None,
         }
     }
 
+    pub(super) fn with_leading_trivia(mut self, trivia: Vec<Trivia>) -> Self {
+        self.leading_trivia = trivia;
+        self
+    }
+
+    pub(super) fn with_doc(mut self, doc: Option<String>) -> Self {
+        self.doc = doc;
+        self
+    }
+
     /// Gives ownership of the `TokenKind`, replacing it by `TokenKind::Taken`
     pub fn take_kind(&mut self) -> TokenKind {
         mem::replace(&mut self.kind, TokenKind::Taken)
     }
 }
 
+/// A piece of a string containing interpolations like `"sum: %(a + b)"`
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    Literal(String),
+    // The expression inside `%( )`, which is parsed from its source code
+    Interpolation(TextSpan),
+}
+
+/// Source code between tokens which does not matter to the parser
+#[derive(Debug, Clone, Getters)]
+pub struct Trivia {
+    kind: TriviaKind,
+    span: TextSpan,
+}
+
+impl Trivia {
+    pub const fn new(kind: TriviaKind, span: TextSpan) -> Self {
+        Self { kind, span }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriviaKind {
+    Whitespace,
+    Comment,
+    DocComment,
+    MultilineComment,
+    // Characters that could not be lexed, which have already been reported
+    Skipped,
+}
+
 impl Into<TextSpan> for &Token {
     fn into(self) -> TextSpan {
         self.span
@@ -50,6 +101,7 @@ pub enum TokenKind {
     At,
     Dollar,
     Colon,
+    Semicolon,
 
     // One char, and optional =
     Plus, PlusEq,
@@ -67,6 +119,7 @@ pub enum TokenKind {
 
     // Two char
     RightArrow,
+    FatArrow,
     BarGt,
     ColonColon,
     Dot, DotDot,
@@ -77,10 +130,201 @@ pub enum TokenKind {
     // Keywords
     Let, Null,
     And, Or, True, False,
-    Function, Type, Struct, Import,
-    If, Then, Else, For, In, Loop, Return, Defer,
+    Function, Type, Struct, Enum, Import, Export,
+    Match, If, Then, Else, For, In, While, Loop, Return, Yield, Break, Defer,
 
     // Special
     EOF, Taken,
-    Ident(String), String(String), Integer(usize), Float(f64),
+    Ident(Symbol), String(String), Char(char),
+    Integer(u128, Option<IntSuffix>), Float(f64, Option<FloatSuffix>),
+    InterpolatedString(Vec<StringPart>),
+}
+
+/// The type written after an integer literal, like the `u8` in `10u8`
+#[rustfmt::skip]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntSuffix {
+    U8, U16, U32, U64, U128,
+    I8, I16, I32, I64, I128,
+}
+
+impl IntSuffix {
+    pub fn parse(suffix: &str) -> Option<Self> {
+        let suffix = match suffix {
+            "u8" => IntSuffix::U8,
+            "u16" => IntSuffix::U16,
+            "u32" => IntSuffix::U32,
+            "u64" => IntSuffix::U64,
+            "u128" => IntSuffix::U128,
+            "i8" => IntSuffix::I8,
+            "i16" => IntSuffix::I16,
+            "i32" => IntSuffix::I32,
+            "i64" => IntSuffix::I64,
+            "i128" => IntSuffix::I128,
+            _ => return None,
+        };
+        Some(suffix)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IntSuffix::U8 => "u8",
+            IntSuffix::U16 => "u16",
+            IntSuffix::U32 => "u32",
+            IntSuffix::U64 => "u64",
+            IntSuffix::U128 => "u128",
+            IntSuffix::I8 => "i8",
+            IntSuffix::I16 => "i16",
+            IntSuffix::I32 => "i32",
+            IntSuffix::I64 => "i64",
+            IntSuffix::I128 => "i128",
+        }
+    }
+
+    /// The largest value a literal of the type can have. For signed types it is one more than
+    /// the largest value of the type, since the literal can be negated.
+    pub fn max(&self) -> u128 {
+        match self {
+            IntSuffix::U8 => u8::MAX as u128,
+            IntSuffix::U16 => u16::MAX as u128,
+            IntSuffix::U32 => u32::MAX as u128,
+            IntSuffix::U64 => u64::MAX as u128,
+            IntSuffix::U128 => u128::MAX,
+            IntSuffix::I8 => i8::MIN.unsigned_abs() as u128,
+            IntSuffix::I16 => i16::MIN.unsigned_abs() as u128,
+            IntSuffix::I32 => i32::MIN.unsigned_abs() as u128,
+            IntSuffix::I64 => i64::MIN.unsigned_abs() as u128,
+            IntSuffix::I128 => i128::MIN.unsigned_abs(),
+        }
+    }
+}
+
+/// The type written after a float literal, like the `f32` in `3.5f32`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FloatSuffix {
+    F32,
+    F64,
+}
+
+impl FloatSuffix {
+    pub fn parse(suffix: &str) -> Option<Self> {
+        match suffix {
+            "f32" => Some(FloatSuffix::F32),
+            "f64" => Some(FloatSuffix::F64),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FloatSuffix::F32 => "f32",
+            FloatSuffix::F64 => "f64",
+        }
+    }
+}
+
+impl TokenKind {
+    /// The source text of tokens that are always written the same way
+    pub fn text(&self) -> Option<&'static str> {
+        let text = match self {
+            TokenKind::LeftParen => "(",
+            TokenKind::RightParen => ")",
+            TokenKind::LeftCurly => "{",
+            TokenKind::RightCurly => "}",
+            TokenKind::LeftSquare => "[",
+            TokenKind::RightSquare => "]",
+            TokenKind::Comma => ",",
+            TokenKind::Quest => "?",
+            TokenKind::At => "@",
+            TokenKind::Dollar => "$",
+            TokenKind::Colon => ":",
+            TokenKind::Semicolon => ";",
+            TokenKind::Plus => "+",
+            TokenKind::PlusEq => "+=",
+            TokenKind::Minus => "-",
+            TokenKind::MinusEq => "-=",
+            TokenKind::Percent => "%",
+            TokenKind::PercentEq => "%=",
+            TokenKind::Slash => "/",
+            TokenKind::SlashEq => "/=",
+            TokenKind::Asterisk => "*",
+            TokenKind::AsteriskEq => "*=",
+            TokenKind::Bang => "!",
+            TokenKind::BangEq => "!=",
+            TokenKind::Equal => "=",
+            TokenKind::EqualEqual => "==",
+            TokenKind::Greater => ">",
+            TokenKind::GreaterEqual => ">=",
+            TokenKind::Less => "<",
+            TokenKind::LessEqual => "<=",
+            TokenKind::Amp => "&",
+            TokenKind::AmpEq => "&=",
+            TokenKind::Bar => "|",
+            TokenKind::BarEq => "|=",
+            TokenKind::Caret => "^",
+            TokenKind::CaretEq => "^=",
+            TokenKind::RightArrow => "->",
+            TokenKind::FatArrow => "=>",
+            TokenKind::BarGt => "|>",
+            TokenKind::ColonColon => "::",
+            TokenKind::Dot => ".",
+            TokenKind::DotDot => "..",
+            TokenKind::AsteriskAsterisk => "**",
+            TokenKind::AsteriskAsteriskEq => "**=",
+            TokenKind::Let => "let",
+            TokenKind::Null => "null",
+            TokenKind::And => "and",
+            TokenKind::Or => "or",
+            TokenKind::True => "true",
+            TokenKind::False => "false",
+            TokenKind::Function => "fn",
+            TokenKind::Type => "type",
+            TokenKind::Struct => "struct",
+            TokenKind::Enum => "enum",
+            TokenKind::Import => "import",
+            TokenKind::Export => "export",
+            TokenKind::Match => "match",
+            TokenKind::If => "if",
+            TokenKind::Then => "then",
+            TokenKind::Else => "else",
+            TokenKind::For => "for",
+            TokenKind::In => "in",
+            TokenKind::While => "while",
+            TokenKind::Loop => "loop",
+            TokenKind::Return => "return",
+            TokenKind::Yield => "yield",
+            TokenKind::Break => "break",
+            TokenKind::Defer => "defer",
+            TokenKind::EOF
+            | TokenKind::Taken
+            | TokenKind::Ident(_)
+            | TokenKind::String(_)
+            | TokenKind::InterpolatedString(_)
+            | TokenKind::Char(_)
+            | TokenKind::Integer(..)
+            | TokenKind::Float(..) => return None,
+        };
+
+        Some(text)
+    }
+}
+
+impl fmt::Display for TokenKind {
+    /// Describes the token for users, quoting its source text if it is always the same
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(text) = self.text() {
+            return write!(f, "'{}'", text);
+        }
+
+        match self {
+            TokenKind::EOF => write!(f, "end of file"),
+            TokenKind::Ident(_) => write!(f, "identifier"),
+            TokenKind::String(_) | TokenKind::InterpolatedString(_) => write!(f, "string literal"),
+            TokenKind::Char(_) => write!(f, "char literal"),
+            TokenKind::Integer(..) => write!(f, "integer literal"),
+            TokenKind::Float(..) => write!(f, "float literal"),
+            TokenKind::Taken => write!(f, "taken token"),
+            _ => unreachable!(),
+        }
+    }
 }
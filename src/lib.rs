@@ -5,6 +5,7 @@ pub mod cst;
 pub mod diagnostics;
 pub mod lex;
 // pub mod package;
+pub mod symbol;
 pub mod text;
 pub mod types;
 
@@ -1,21 +1,90 @@
 use std::{fs, process, io};
+use std::io::IsTerminal;
 
 use hel::lex::Lexer;
 use hel::cst::Parser;
+use hel::diagnostics::Renderer;
+use hel::text::SourceMap;
+
+/// How diagnostics are printed, chosen with `--format`
+enum Format {
+    Human,
+    Json,
+    Sarif,
+}
 
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
+    let mut format = Format::Human;
+    let mut filename = None;
 
-    if let Some(filename) = args.get(1) {
-        let input = match fs::read_to_string(filename) {
+    let mut args = std::env::args().skip(1).peekable();
+    // `hel fix <file>` applies the machine-applicable suggestions to the file before reporting
+    let fix = args.next_if(|arg| arg == "fix").is_some();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = match args.next().as_deref() {
+                    Some("human") => Format::Human,
+                    Some("json") => Format::Json,
+                    Some("sarif") => Format::Sarif,
+                    _ => {
+                        eprintln!("--format must be followed by human, json or sarif");
+                        process::exit(-1);
+                    }
+                }
+            }
+            _ => filename = Some(arg),
+        }
+    }
+
+    if let Some(filename) = filename {
+        let input = match fs::read_to_string(&filename) {
             Ok(input) => input,
             Err(err) => {
                 eprintln!("{}", err);
                 process::exit(-1);
             }
         };
-        let mut parser = Parser::new(Lexer::from(&input));
-        eprintln!("{:#?}", parser.parse_expr());
+        let mut sources = SourceMap::default();
+        let mut file = sources.add(&filename, input);
+        if fix {
+            let mut parser = Parser::new(Lexer::from(sources.file(file)));
+            parser.parse_module();
+            let (fixed, count) = parser.diagnostics().apply_fixes(sources.file(file));
+            if count > 0 {
+                if let Err(err) = fs::write(&filename, &fixed) {
+                    eprintln!("{}", err);
+                    process::exit(-1);
+                }
+                eprintln!("Applied {} fixes to {}", count, filename);
+            }
+            sources = SourceMap::default();
+            file = sources.add(&filename, fixed);
+        }
+        let mut parser = Parser::new(Lexer::from(sources.file(file)));
+
+        let result = parser.parse_package();
+        let diagnostics = parser.diagnostics();
+
+        // The machine readable formats are written to stdout on their own
+        match format {
+            Format::Human => {
+                let renderer = Renderer::new(&sources, io::stderr().is_terminal());
+                for diagnostic in diagnostics.iter() {
+                    eprintln!("{}", renderer.render(diagnostic));
+                }
+
+                if !fix {
+                    println!("{:#?}", result);
+                }
+            }
+            Format::Json => println!("{:#}", diagnostics.to_json(&sources)),
+            Format::Sarif => println!("{:#}", diagnostics.to_sarif(&sources)),
+        }
+
+        if diagnostics.has_errors() {
+            process::exit(1);
+        }
     } else {
         let mut line = String::new();
         while line != "exit" {
@@ -27,17 +96,18 @@ fn main() {
                 }
             }
 
-            let mut parser = Parser::new(Lexer::from(&line));
+            let mut sources = SourceMap::default();
+            let file = sources.add("<stdin>", line.clone());
+            let mut parser = Parser::new(Lexer::from(sources.file(file)));
 
             let result = parser.parse_type();
 
+            let renderer = Renderer::new(&sources, io::stderr().is_terminal());
             for diagnostic in parser.diagnostics().iter() {
-                eprintln!("{:?}", diagnostic);
+                eprintln!("{}", renderer.render(diagnostic));
             }
 
-            if let Some(result) = result {
-                println!("{:#?}", result);
-            }
+            println!("{:#?}", result);
 
             line.clear();
         }
@@ -1,3 +1,5 @@
+mod diagnostics;
 mod lex;
 mod text;
 mod parse;
+mod symbol;
@@ -0,0 +1,332 @@
+use crate::cst::Parser;
+use crate::diagnostics::{Applicability, Diagnostic, Diagnostics, Renderer, Severity};
+use crate::text::{SourceMap, TextSpan};
+
+fn source_map(source: &str) -> SourceMap {
+    let mut sources = SourceMap::default();
+    sources.add("test.hel", source.into());
+    sources
+}
+
+fn render(source: &str, span: TextSpan, colour: bool) -> String {
+    let sources = source_map(source);
+    let mut diagnostics = Diagnostics::default();
+    diagnostics.number_out_of_range(span, "u8");
+    Renderer::new(&sources, colour).render(&diagnostics[0])
+}
+
+#[test]
+fn render_single_line() {
+    assert_eq!(
+        render("let a = 1 < 2 < 3\n", TextSpan::new(14, 15), false),
+        "error[E0019]: Number literal out of range for u8
+ --> test.hel:1:15
+  |
+1 | let a = 1 < 2 < 3
+  |               ^
+"
+    );
+    assert_eq!(
+        render("a\n\tb == c\n", TextSpan::new(3, 9), false),
+        "error[E0019]: Number literal out of range for u8
+ --> test.hel:2:2
+  |
+2 |     b == c
+  |     ^^^^^^
+"
+    );
+}
+
+#[test]
+fn render_multi_line() {
+    assert_eq!(
+        render("a\nbcd\nef\n", TextSpan::new(3, 7), false),
+        "error[E0019]: Number literal out of range for u8
+ --> test.hel:2:2
+  |
+2 | bcd
+  |  ^^
+3 | ef
+  | ^
+"
+    );
+
+    let source = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+    assert_eq!(
+        render(source, TextSpan::new(0, source.len()), false),
+        "error[E0019]: Number literal out of range for u8
+  --> test.hel:1:1
+   |
+ 1 | 1
+   | ^
+ 2 | 2
+   | ^
+...
+ 9 | 9
+   | ^
+10 | 10
+   | ^^
+"
+    );
+}
+
+#[test]
+fn render_at_eof() {
+    for source in &["fn f() =\n", "fn f() ="] {
+        assert_eq!(
+            render(source, TextSpan::new(8, 8), false),
+            "error[E0019]: Number literal out of range for u8
+ --> test.hel:1:9
+  |
+1 | fn f() =
+  |         ^
+"
+        );
+    }
+}
+
+#[test]
+fn render_multi_byte() {
+    let input = "let varför = \"å\\ö\" < 2 < 3\n";
+    let mut parser = Parser::new(input.into());
+    parser.parse_module();
+    let sources = source_map(input);
+    let rendered: Vec<_> = parser
+        .diagnostics()
+        .iter()
+        .map(|d| Renderer::new(&sources, false).render(d))
+        .collect();
+    assert_eq!(
+        rendered[0],
+        "error[E0002]: Invalid escape character: ö
+ --> test.hel:1:16
+  |
+1 | let varför = \"å\\ö\" < 2 < 3
+  |                ^^
+  = help: the valid escape sequences are \\\", \\', \\\\, \\n, \\t, \\r, \\0, \\%, \\xNN and \\u{NNNN}
+  = help: escape the backslash to keep it
+"
+    );
+    assert!(rendered[1].starts_with(
+        "error[E0007]: Comparison operators cannot be chained
+ --> test.hel:1:24"
+    ));
+
+    let sarif = parser.diagnostics().to_sarif(&sources);
+    let region = &sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"];
+    assert_eq!(region["startColumn"], 16);
+    assert_eq!(region["endColumn"], 18);
+}
+
+#[test]
+fn render_colour() {
+    let rendered = render("1 < 2 < 3", TextSpan::new(6, 7), true);
+    assert!(rendered.starts_with("\x1b[1;31merror[E0019]\x1b[0m: "));
+    assert!(rendered.ends_with("\x1b[1;31m^\x1b[0m\n"));
+}
+
+#[test]
+fn render_labels_and_notes() {
+    let input = "let a = 1 <\n    2 < 3\n";
+    let mut parser = Parser::new(input.into());
+    parser.parse_module();
+    let diagnostics = parser.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics.has_errors());
+
+    let sources = source_map(input);
+    assert_eq!(
+        Renderer::new(&sources, false).render(&diagnostics[0]),
+        "error[E0007]: Comparison operators cannot be chained
+ --> test.hel:2:7
+  |
+1 | let a = 1 <
+  |           - the previous comparison
+2 |     2 < 3
+  |       ^
+  = help: use parentheses, or split it into two comparisons joined with 'and'
+"
+    );
+}
+
+#[test]
+fn render_across_files() {
+    let mut sources = SourceMap::default();
+    sources.add("main.hel", "import lib\nlet a = lib.f\n".into());
+    let lib = sources.add("lib.hel", "fn g() = 1\n".into());
+    let start = sources.file(lib).start();
+
+    let diagnostic = Diagnostic::error(
+        "E0019",
+        "Number literal out of range for u8",
+        TextSpan::new(23, 24),
+    )
+    .label(TextSpan::new(start + 3, start + 4), "similarly named");
+    assert_eq!(
+        Renderer::new(&sources, false).render(&diagnostic),
+        "error[E0019]: Number literal out of range for u8
+ --> main.hel:2:13
+  |
+2 | let a = lib.f
+  |             ^
+  |
+ ::: lib.hel:1:4
+  |
+1 | fn g() = 1
+  |    - similarly named
+"
+    );
+}
+
+#[test]
+fn warnings() {
+    let input = "fn f() = {}";
+    let mut parser = Parser::new(input.into());
+    parser.parse_module();
+    let diagnostics = parser.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity(), Severity::Warning);
+    assert!(!diagnostics.has_errors());
+
+    let sources = source_map(input);
+    let rendered = Renderer::new(&sources, false).render(&diagnostics[0]);
+    assert!(rendered.starts_with("warning[W0001]: "));
+}
+
+#[test]
+fn json() {
+    let input = "let a = )\n";
+    let mut parser = Parser::new(input.into());
+    parser.parse_module();
+
+    let sources = source_map(input);
+    let json = parser.diagnostics().to_json(&sources);
+    assert_eq!(json["version"], 1);
+    let diagnostic = &json["diagnostics"][0];
+    assert_eq!(diagnostic["code"], "E0006");
+    assert_eq!(diagnostic["severity"], "error");
+    assert_eq!(diagnostic["file"], "test.hel");
+    assert_eq!(diagnostic["range"]["start"], 8);
+    assert_eq!(diagnostic["range"]["end"], 9);
+    assert_eq!(diagnostic["start"]["line"], 1);
+    assert_eq!(diagnostic["start"]["column"], 9);
+    assert_eq!(diagnostic["end"]["column"], 10);
+    assert_eq!(diagnostic["labels"][0]["message"], "expected expression");
+    assert_eq!(diagnostic["labels"][0]["range"], diagnostic["range"]);
+}
+
+#[test]
+fn sarif() {
+    let input = "let a = 1 < 2 < 3\nfn f() = {}\n";
+    let mut parser = Parser::new(input.into());
+    parser.parse_module();
+
+    let sources = source_map(input);
+    let sarif = parser.diagnostics().to_sarif(&sources);
+    assert_eq!(sarif["version"], "2.1.0");
+    let run = &sarif["runs"][0];
+    assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "E0007");
+    assert_eq!(run["tool"]["driver"]["rules"][1]["id"], "W0001");
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["ruleId"], "E0007");
+    assert_eq!(results[0]["level"], "error");
+    let region = &results[0]["locations"][0]["physicalLocation"]["region"];
+    assert_eq!(region["startLine"], 1);
+    assert_eq!(region["startColumn"], 15);
+    assert_eq!(region["endColumn"], 16);
+    assert_eq!(
+        results[0]["relatedLocations"][0]["message"]["text"],
+        "the previous comparison"
+    );
+    assert_eq!(results[1]["ruleIndex"], 1);
+    assert_eq!(results[1]["level"], "warning");
+    let fix = &results[1]["fixes"][0];
+    assert_eq!(fix["description"]["text"], "remove the '='");
+    let replacement = &fix["artifactChanges"][0]["replacements"][0];
+    assert_eq!(replacement["deletedRegion"]["startLine"], 2);
+    assert_eq!(replacement["deletedRegion"]["startColumn"], 8);
+    assert_eq!(replacement["deletedRegion"]["endColumn"], 10);
+    assert_eq!(replacement["insertedContent"]["text"], "");
+}
+
+fn fix(input: &str) -> (String, usize) {
+    let sources = source_map(input);
+    let mut parser = Parser::new(input.into());
+    parser.parse_module();
+    parser
+        .diagnostics()
+        .apply_fixes(sources.file(sources.find(0)))
+}
+
+#[test]
+fn suggestions() {
+    let input = "fn f(a: int b: int) = a\n";
+    let mut parser = Parser::new(input.into());
+    parser.parse_module();
+    let suggestion = &parser.diagnostics()[0].suggestions()[0];
+    assert_eq!(suggestion.message(), "add a ','");
+    assert_eq!(*suggestion.span(), TextSpan::new(11, 11));
+    assert_eq!(suggestion.replacement(), ",");
+    assert_eq!(
+        *suggestion.applicability(),
+        Applicability::MachineApplicable
+    );
+
+    let sources = source_map(input);
+    let rendered = Renderer::new(&sources, false).render(&parser.diagnostics()[0]);
+    assert!(rendered.ends_with("  = help: add a ','\n"));
+
+    let json = parser.diagnostics().to_json(&sources);
+    let suggestion = &json["diagnostics"][0]["suggestions"][0];
+    assert_eq!(suggestion["replacement"], ",");
+    assert_eq!(suggestion["applicability"], "machine-applicable");
+    assert_eq!(suggestion["start"]["column"], 12);
+
+    let mut parser = Parser::new("g(a b)".into());
+    parser.parse_expr();
+    let suggestion = &parser.diagnostics()[0].suggestions()[0];
+    assert_eq!(*suggestion.applicability(), Applicability::MaybeIncorrect);
+
+    // The item before the missing comma failed to parse, so adding one would not help
+    let mut parser = Parser::new("f(1 + ]\n 2)".into());
+    parser.parse_expr();
+    assert!(!parser.diagnostics().is_empty());
+    assert!(parser
+        .diagnostics()
+        .iter()
+        .all(|diagnostic| diagnostic.suggestions().is_empty()));
+}
+
+#[test]
+fn apply_fixes() {
+    assert_eq!(
+        fix("fn f(a: int b: int) = a\n"),
+        ("fn f(a: int, b: int) = a\n".to_string(), 1)
+    );
+    assert_eq!(fix("fn f() = {}\n"), ("fn f() {}\n".to_string(), 1));
+    // Whether `g` takes one or two arguments is up to the user
+    assert_eq!(
+        fix("fn f(a: int b: int) = {\n    g(a b)\n}\n"),
+        ("fn f(a: int, b: int) {\n    g(a b)\n}\n".to_string(), 2)
+    );
+    assert_eq!(
+        fix("let p = P @{ x: 1 y: 2 }\n"),
+        ("let p = P @{ x: 1, y: 2 }\n".to_string(), 1)
+    );
+    // Nothing is added after something that failed to parse
+    assert_eq!(
+        fix("fn f(a: ] b: int) = a\n"),
+        ("fn f(a: ] b: int) = a\n".to_string(), 0)
+    );
+    // Changing the escape could also be what was meant, so it is left to the user
+    assert_eq!(
+        fix("let a = \"\\q\"\n"),
+        ("let a = \"\\q\"\n".to_string(), 0)
+    );
+    assert_eq!(
+        fix("let a = \"ä\"\nfn f() = {}\n").0,
+        "let a = \"ä\"\nfn f() {}\n"
+    );
+}
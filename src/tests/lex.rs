@@ -1,4 +1,9 @@
-use crate::lex::{Lexer, TokenKind};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+use crate::lex::{FloatSuffix, IntSuffix, Lexer, StringPart, TokenKind, TriviaKind};
+use crate::text::TextSpan;
 
 #[test]
 fn single_tokens() {
@@ -63,6 +68,474 @@ fn whitespace() {
     }
 }
 
+#[test]
+fn multi_byte_spans() {
+    let input = "varför = \"𝄞\" #( ö )# ä";
+    let mut lexer = Lexer::from(input);
+    let tokens: Vec<_> = (0..4).map(|_| lexer.eat()).collect();
+    let texts: Vec<_> = tokens
+        .iter()
+        .map(|t| lexer.source_text(*t.span()))
+        .collect();
+    assert_eq!(texts, ["varför", "=", "\"𝄞\"", "ä"]);
+    assert_eq!(*tokens[0].span(), TextSpan::new(0, 7));
+    assert_eq!(
+        *tokens[3].span(),
+        TextSpan::new(input.len() - 2, input.len())
+    );
+    assert_eq!(*lexer.eat().kind(), TokenKind::EOF);
+
+    let mut lexer = Lexer::from("\"\\ö\" €");
+    lexer.eat();
+    lexer.eat();
+    let spans: Vec<_> = lexer.diagnostics().iter().map(|d| *d.span()).collect();
+    assert_eq!(spans, [TextSpan::new(1, 4), TextSpan::new(6, 9)]);
+}
+
+#[test]
+fn interpolated_strings() {
+    let input = r#""sum: %(a + f(")")) done" b"#;
+    let mut lexer = Lexer::from(input).with_trivia();
+    let string = lexer.eat();
+    assert_eq!(
+        *string.kind(),
+        TokenKind::InterpolatedString(vec![
+            StringPart::Literal("sum: ".into()),
+            StringPart::Interpolation(TextSpan::new(8, 18)),
+            StringPart::Literal(" done".into()),
+        ])
+    );
+    assert_eq!(*string.span(), TextSpan::new(0, 25));
+    let b = lexer.eat();
+    assert_eq!(*b.kind(), TokenKind::Ident("b".into()));
+    assert!(b.whitespace_before());
+    assert_eq!(b.leading_trivia().len(), 1);
+    assert!(lexer.diagnostics().is_empty());
+
+    let mut lexer = Lexer::from(r#""\%(a)""#);
+    assert_eq!(*lexer.eat().kind(), TokenKind::String("%(a)".into()));
+
+    let mut lexer = Lexer::from(r#""%(a"#);
+    assert_eq!(*lexer.eat().kind(), TokenKind::InterpolatedString(vec![]));
+    assert_eq!(lexer.diagnostics()[0].code(), "E0001");
+}
+
+fn lex_string(input: &str) -> TokenKind {
+    let mut lexer = Lexer::from(input);
+    let kind = lexer.eat().kind().clone();
+    assert!(
+        lexer.diagnostics().is_empty(),
+        "Input: {}\n{:#?}",
+        input,
+        lexer.diagnostics()
+    );
+    assert_eq!(*lexer.eat().kind(), TokenKind::EOF, "Input: {}", input);
+    kind
+}
+
+#[test]
+fn escape_sequences() {
+    #[rustfmt::skip]
+    let cases = [
+        (r#""\"\\\n\t\r""#,       "\"\\\n\t\r"),
+        (r#""\0\%""#,             "\0%"),
+        (r#""\x41\x7f\x0A""#,     "A\x7f\n"),
+        (r#""\u{48}\u{e4}""#,     "Hä"),
+        (r#""\u{1D11E}\u{10FFFF}""#, "𝄞\u{10FFFF}"),
+    ];
+    for (input, expected) in cases.iter() {
+        assert_eq!(lex_string(input), TokenKind::String((*expected).into()));
+    }
+
+    #[rustfmt::skip]
+    let invalid = [
+        (r#""\q""#,           "E0002", (1, 3)),
+        (r#""a\x4""#,         "E0009", (2, 5)),
+        (r#""\xG0""#,         "E0009", (1, 3)),
+        (r#""\x80""#,         "E0010", (1, 5)),
+        (r#""\u41""#,         "E0009", (1, 3)),
+        (r#""\u{}""#,         "E0009", (1, 4)),
+        (r#""\u{41""#,        "E0009", (1, 6)),
+        (r#""\u{1234567}""#,  "E0009", (1, 10)),
+        (r#""\u{D800}""#,     "E0011", (1, 9)),
+        (r#""\u{110000}""#,   "E0011", (1, 11)),
+    ];
+    for (input, code, (start, end)) in invalid.iter() {
+        let mut lexer = Lexer::from(*input);
+        lexer.eat();
+        let diagnostics = lexer.diagnostics();
+        assert_eq!(diagnostics.len(), 1, "Input: {}", input);
+        assert_eq!(diagnostics[0].code(), *code, "Input: {}", input);
+        assert_eq!(
+            *diagnostics[0].span(),
+            TextSpan::new(*start, *end),
+            "Input: {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn number_literals() {
+    use TokenKind::{Float, Integer};
+    #[rustfmt::skip]
+    let cases = [
+        ("1_000_000",       Integer(1_000_000, None)),
+        ("0o17",            Integer(0o17, None)),
+        ("0b1010_1010",     Integer(0b1010_1010, None)),
+        ("0xFF_ff",         Integer(0xffff, None)),
+        ("10u8",            Integer(10, Some(IntSuffix::U8))),
+        ("0xffi64",         Integer(0xff, Some(IntSuffix::I64))),
+        ("128i8",           Integer(128, Some(IntSuffix::I8))),
+        ("340282366920938463463374607431768211455",
+                            Integer(u128::MAX, None)),
+        ("3.5f32",          Float(3.5, Some(FloatSuffix::F32))),
+        ("1e-5",            Float(1e-5, None)),
+        ("2.5E+3",          Float(2.5e3, None)),
+        ("1_000.000_1",     Float(1000.0001, None)),
+        ("7f64",            Float(7.0, Some(FloatSuffix::F64))),
+    ];
+    for (input, expected) in cases.iter() {
+        assert_eq!(lex_string(input), *expected, "Input: {}", input);
+    }
+
+    // A dot only makes a float when a digit follows it
+    let mut lexer = Lexer::from("1..2 3.abs");
+    let kinds: Vec<_> = (0..5).map(|_| lexer.eat().kind().clone()).collect();
+    assert_eq!(
+        kinds,
+        [
+            Integer(1, None),
+            TokenKind::DotDot,
+            Integer(2, None),
+            Integer(3, None),
+            TokenKind::Dot
+        ]
+    );
+
+    #[rustfmt::skip]
+    let invalid = [
+        ("0b102",                                   "E0016", (4, 5)),
+        ("0o8",                                     "E0016", (2, 3)),
+        ("0x",                                      "E0017", (0, 2)),
+        ("1e",                                      "E0017", (0, 2)),
+        ("1e+",                                     "E0017", (0, 3)),
+        ("12ab",                                    "E0018", (2, 4)),
+        ("1.5u8",                                   "E0018", (3, 5)),
+        ("0b1f32",                                  "E0018", (3, 6)),
+        ("256u8",                                   "E0019", (0, 5)),
+        ("129i8",                                   "E0019", (0, 5)),
+        ("340282366920938463463374607431768211456", "E0019", (0, 39)),
+        ("1e39f32",                                 "E0019", (0, 7)),
+        ("1e309",                                   "E0019", (0, 5)),
+    ];
+    for (input, code, (start, end)) in invalid.iter() {
+        let mut lexer = Lexer::from(*input);
+        assert!(matches!(lexer.eat().kind(), Integer(..) | Float(..)));
+        assert_eq!(*lexer.eat().kind(), TokenKind::EOF, "Input: {}", input);
+        let diagnostics = lexer.diagnostics();
+        assert_eq!(diagnostics.len(), 1, "Input: {}", input);
+        assert_eq!(diagnostics[0].code(), *code, "Input: {}", input);
+        assert_eq!(
+            *diagnostics[0].span(),
+            TextSpan::new(*start, *end),
+            "Input: {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn char_literals() {
+    #[rustfmt::skip]
+    let cases = [
+        ("'a'",         'a'),
+        ("'ä'",         'ä'),
+        (r"'\n'",       '\n'),
+        (r"'\''",       '\''),
+        ("'\"'",        '"'),
+        (r"'\x7F'",     '\x7F'),
+        (r"'\u{1F600}'", '😀'),
+    ];
+    for (input, expected) in cases.iter() {
+        assert_eq!(lex_string(input), TokenKind::Char(*expected));
+    }
+
+    #[rustfmt::skip]
+    let invalid = [
+        ("''",          "E0015", (0, 2)),
+        ("'ab'",        "E0015", (0, 4)),
+        ("'a\nb",       "E0014", (0, 1)),
+        (r"'\q'",       "E0002", (1, 3)),
+    ];
+    for (input, code, (start, end)) in invalid.iter() {
+        let mut lexer = Lexer::from(*input);
+        lexer.eat();
+        let diagnostics = lexer.diagnostics();
+        assert_eq!(diagnostics.len(), 1, "Input: {}", input);
+        assert_eq!(diagnostics[0].code(), *code, "Input: {}", input);
+        assert_eq!(
+            *diagnostics[0].span(),
+            TextSpan::new(*start, *end),
+            "Input: {}",
+            input
+        );
+    }
+
+    let mut lexer = Lexer::from("'ab'");
+    lexer.eat();
+    assert_eq!(
+        lexer.diagnostics()[0].suggestions()[0].replacement(),
+        "\"ab\""
+    );
+}
+
+#[test]
+fn raw_strings() {
+    assert_eq!(
+        lex_string(r#"r"C:\path %(x)""#),
+        TokenKind::String(r"C:\path %(x)".into())
+    );
+    assert_eq!(
+        lex_string(r###"r##"a "# b"##"###),
+        TokenKind::String(r##"a "# b"##.into())
+    );
+    assert_eq!(lex_string(r#"r"""#), TokenKind::String("".into()));
+
+    // Only an r right before the quote starts a raw string
+    let mut lexer = Lexer::from(r#"r "a""#);
+    assert_eq!(*lexer.eat().kind(), TokenKind::Ident("r".into()));
+
+    let mut lexer = Lexer::from(r##"r#"a""##);
+    lexer.eat();
+    assert_eq!(lexer.diagnostics()[0].code(), "E0001");
+}
+
+#[test]
+fn multi_line_strings() {
+    let input = "\"\"\"\n    first\n      second\n\n    \\tthird %(x)\n    \"\"\"";
+    assert_eq!(
+        lex_string(input),
+        TokenKind::InterpolatedString(vec![
+            StringPart::Literal("first\n  second\n\n\tthird ".into()),
+            StringPart::Interpolation(TextSpan::new(42, 43)),
+        ])
+    );
+    assert_eq!(
+        lex_string("\"\"\"\n  \"quoted\" \"\"\n  \"\"\""),
+        TokenKind::String("\"quoted\" \"\"".into())
+    );
+    assert_eq!(
+        lex_string("\"\"\"\r\n  a\r\n  \"\"\""),
+        TokenKind::String("a".into())
+    );
+
+    let mut lexer = Lexer::from("\"\"\" a\n    b\n  c\n    \"\"\"");
+    lexer.eat();
+    let codes: Vec<_> = lexer.diagnostics().iter().map(|d| d.code()).collect();
+    assert_eq!(codes, ["E0012", "E0013"]);
+    assert_eq!(*lexer.diagnostics()[1].span(), TextSpan::new(12, 14));
+}
+
+/// Puts the source code back together from the tokens and trivia
+fn reconstruct(input: &str) -> String {
+    let mut lexer = Lexer::from(input).with_trivia();
+    let mut output = String::new();
+    loop {
+        let token = lexer.eat();
+        for trivia in token.leading_trivia() {
+            output.push_str(lexer.source_text(*trivia.span()));
+        }
+        output.push_str(lexer.source_text(*token.span()));
+        if *token.kind() == TokenKind::EOF {
+            return output;
+        }
+    }
+}
+
+#[test]
+fn trivia() {
+    let mut lexer = Lexer::from("a # one\n#( two )#\tb").with_trivia();
+    assert!(lexer.eat().leading_trivia().is_empty());
+    let b = lexer.eat();
+    let trivia: Vec<_> = b
+        .leading_trivia()
+        .iter()
+        .map(|trivia| (*trivia.kind(), lexer.source_text(*trivia.span())))
+        .collect();
+    assert_eq!(
+        trivia,
+        [
+            (TriviaKind::Whitespace, " "),
+            (TriviaKind::Comment, "# one"),
+            (TriviaKind::Whitespace, "\n"),
+            (TriviaKind::MultilineComment, "#( two )#"),
+            (TriviaKind::Whitespace, "\t"),
+        ]
+    );
+
+    // Without trivia, it is simply dropped
+    let mut lexer = Lexer::from("a # one\n b");
+    lexer.eat();
+    assert!(lexer.eat().leading_trivia().is_empty());
+}
+
+#[test]
+fn comments() {
+    let mut lexer = Lexer::from("a #( b #( c )# d )# e #(f)#g # h )#\ni");
+    let idents: Vec<_> = (0..4).map(|_| lexer.eat().kind().clone()).collect();
+    assert_eq!(
+        idents,
+        ["a", "e", "g", "i"]
+            .iter()
+            .map(|ident| TokenKind::Ident((*ident).into()))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(*lexer.eat().kind(), TokenKind::EOF);
+    assert!(lexer.diagnostics().is_empty());
+
+    let mut lexer = Lexer::from("a #( #( )# b");
+    assert_eq!(*lexer.eat().kind(), TokenKind::Ident("a".into()));
+    assert_eq!(*lexer.eat().kind(), TokenKind::EOF);
+    assert_eq!(lexer.diagnostics()[0].code(), "E0003");
+    assert_eq!(*lexer.diagnostics()[0].span(), TextSpan::new(2, 4));
+}
+
+#[test]
+fn doc_comments() {
+    let mut lexer =
+        Lexer::from("## Adds one\r\n##\n##  to `n`\n# not docs\nfn ## more\n\ta").with_trivia();
+    let token = lexer.eat();
+    assert_eq!(*token.kind(), TokenKind::Function);
+    assert_eq!(token.doc().as_deref(), Some("Adds one\n\n to `n`"));
+    let kinds: Vec<_> = token
+        .leading_trivia()
+        .iter()
+        .map(|trivia| *trivia.kind())
+        .collect();
+    assert_eq!(kinds[0], TriviaKind::DocComment);
+    assert!(kinds.contains(&TriviaKind::Comment));
+
+    let token = lexer.eat();
+    assert_eq!(token.doc().as_deref(), Some("more"));
+    assert_eq!(*lexer.eat().doc(), None);
+}
+
+#[test]
+fn lookahead() {
+    let mut lexer = Lexer::from("a b ¤ c");
+    assert_eq!(*lexer.peek_nth(2).kind(), TokenKind::Ident("c".into()));
+    assert_eq!(*lexer.peek_nth(5).kind(), TokenKind::EOF);
+    assert_eq!(*lexer.peek().kind(), TokenKind::Ident("a".into()));
+    assert_eq!(lexer.diagnostics().len(), 1);
+
+    let idents: Vec<_> = lexer.by_ref().map(|token| token.kind().clone()).collect();
+    assert_eq!(
+        idents,
+        ["a", "b", "c"]
+            .iter()
+            .map(|ident| TokenKind::Ident((*ident).into()))
+            .collect::<Vec<_>>()
+    );
+    // The EOF token is left for the parser
+    assert_eq!(*lexer.eat().kind(), TokenKind::EOF);
+}
+
+#[test]
+fn checkpoint_and_rewind() {
+    let mut lexer = Lexer::from("a ¤ b ## doc\nc").with_trivia();
+    lexer.eat();
+    let checkpoint = lexer.checkpoint();
+    let b = lexer.eat();
+    lexer.peek_nth(1);
+    assert_eq!(lexer.diagnostics().len(), 1);
+
+    lexer.rewind(checkpoint);
+    assert!(lexer.diagnostics().is_empty());
+    let again = lexer.eat();
+    assert_eq!(again.kind(), b.kind());
+    assert_eq!(again.span(), b.span());
+    assert_eq!(again.leading_trivia().len(), b.leading_trivia().len());
+    assert_eq!(lexer.previous_end(), b.span().end());
+    assert_eq!(lexer.eat().doc().as_deref(), Some("doc"));
+
+    // Rewinding over peeked tokens gives them back as well
+    let mut lexer = Lexer::from("a b c");
+    lexer.peek_nth(1);
+    let checkpoint = lexer.checkpoint();
+    lexer.eat();
+    lexer.eat();
+    lexer.rewind(checkpoint);
+    assert_eq!(*lexer.eat().kind(), TokenKind::Ident("a".into()));
+    assert_eq!(*lexer.peek_nth(1).kind(), TokenKind::Ident("c".into()));
+
+    // Taking the diagnostics in between doesn't keep later ones around
+    let mut lexer = Lexer::from("¤ a ¤ b ¤ c");
+    lexer.eat();
+    let checkpoint = lexer.checkpoint();
+    lexer.eat();
+    assert_eq!(lexer.take_diagnostics().len(), 2);
+    lexer.eat();
+    assert_eq!(lexer.diagnostics().len(), 1);
+    lexer.rewind(checkpoint);
+    assert!(lexer.diagnostics().is_empty());
+}
+
+#[test]
+fn lossless_round_trip() {
+    let mut paths = vec![];
+    let mut dirs = vec![Path::new(env!("CARGO_MANIFEST_DIR")).join("tests")];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension() == Some(OsStr::new("hel")) {
+                paths.push(path);
+            }
+        }
+    }
+    assert!(!paths.is_empty());
+
+    for path in paths {
+        let input = fs::read_to_string(&path).unwrap();
+        // Every prefix of a file is also lexed, to cover unterminated strings and comments
+        let ends = input
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(Some(input.len()));
+        for end in ends {
+            assert_eq!(
+                reconstruct(&input[..end]),
+                &input[..end],
+                "Round trip of {}",
+                path.display()
+            );
+        }
+    }
+
+    #[rustfmt::skip]
+    let inputs = [
+        "\"unterminated \\", "a ¤ b", "#( #( )# ", "# no newline", "r#\"raw", "\"\"\"\n  a\n",
+        "## doc\r\n##", "#(#)#",
+    ];
+    for input in &inputs {
+        assert_eq!(reconstruct(input), *input);
+    }
+}
+
+#[test]
+fn display_token_kinds() {
+    // Tokens that are always written the same way are displayed as their quoted source text
+    for (text, kind) in basic_tokens().iter() {
+        assert_eq!(kind.to_string(), format!("'{}'", text));
+    }
+    for (_, kind) in tokens_with_values().iter() {
+        assert!(!kind.to_string().starts_with('\''), "{:?}", kind);
+    }
+    assert_eq!(TokenKind::EOF.to_string(), "end of file");
+}
+
 fn require_separation(kind1: &TokenKind, kind2: &TokenKind) -> bool {
     use TokenKind::*;
     match (kind1, kind2) {
@@ -70,56 +543,73 @@ fn require_separation(kind1: &TokenKind, kind2: &TokenKind) -> bool {
         | (Colon, ColonColon)
         | (Plus, Equal)
         | (Plus, EqualEqual)
+        | (Plus, FatArrow)
         | (Minus, Equal)
         | (Minus, EqualEqual)
+        | (Minus, FatArrow)
         | (Asterisk, Equal)
         | (Asterisk, EqualEqual)
+        | (Asterisk, FatArrow)
         | (Minus, Greater)
         | (Minus, GreaterEqual)
         | (Percent, Equal)
         | (Percent, EqualEqual)
+        | (Percent, FatArrow)
         | (Slash, Equal)
         | (Slash, EqualEqual)
+        | (Slash, FatArrow)
         | (Asterisk, Asterisk)
         | (AsteriskAsterisk, Equal)
         | (AsteriskAsterisk, EqualEqual)
+        | (AsteriskAsterisk, FatArrow)
         | (Asterisk, AsteriskEq)
         | (Asterisk, AsteriskAsteriskEq)
         | (Asterisk, AsteriskAsterisk)
         | (Bang, Equal)
         | (Bang, EqualEqual)
+        | (Bang, FatArrow)
         | (Equal, Equal)
         | (Equal, EqualEqual)
+        | (Equal, FatArrow)
+        | (Equal, Greater)
+        | (Equal, GreaterEqual)
         | (Greater, Equal)
         | (Greater, EqualEqual)
+        | (Greater, FatArrow)
         | (Less, Equal)
         | (Less, EqualEqual)
+        | (Less, FatArrow)
         | (Amp, Equal)
         | (Amp, EqualEqual)
+        | (Amp, FatArrow)
         | (Bar, Equal)
         | (Bar, EqualEqual)
+        | (Bar, FatArrow)
         | (Bar, Greater)
         | (Bar, GreaterEqual)
         | (Caret, Equal)
         | (Caret, EqualEqual)
+        | (Caret, FatArrow)
         | (Dot, Dot)
         | (Dot, DotDot) => true,
         (kw1, kw2) if is_keyword(kw1) && is_keyword(kw2) => true,
-        (kw, Integer(_)) if is_keyword(kw) => true,
+        (kw, Integer(..)) if is_keyword(kw) => true,
         (kw, Ident(_)) if is_keyword(kw) => true,
-        (kw, Float(_)) if is_keyword(kw) => true,
-        (Integer(_), Dot) => true,
-        (Integer(_), DotDot) => true,
-        (Integer(_), kw) if is_keyword(kw) => true,
-        (Integer(_), Integer(_)) => true,
-        (Integer(_), Float(_)) => true,
+        (kw, Float(..)) if is_keyword(kw) => true,
+        (Integer(..), Dot) => true,
+        (Integer(..), DotDot) => true,
+        (Integer(..), kw) if is_keyword(kw) => true,
+        (Integer(..), Integer(..)) => true,
+        (Integer(..), Float(..)) => true,
+        (Integer(..), Ident(..)) => true,
         (Ident(_), kw) if is_keyword(kw) => true,
-        (Ident(_), Integer(_)) => true,
+        (Ident(_), Integer(..)) => true,
         (Ident(_), Ident(_)) => true,
-        (Ident(_), Float(_)) => true,
-        (Float(_), kw) if is_keyword(kw) => true,
-        (Float(_), Integer(_)) => true,
-        (Float(_), Float(_)) => true,
+        (Ident(_), Float(..)) => true,
+        (Float(..), kw) if is_keyword(kw) => true,
+        (Float(..), Integer(..)) => true,
+        (Float(..), Ident(..)) => true,
+        (Float(..), Float(..)) => true,
         _ => false,
     }
 }
@@ -136,30 +626,38 @@ fn is_keyword(kind: &TokenKind) -> bool {
         || *kind == Function
         || *kind == Type
         || *kind == Struct
+        || *kind == Enum
         || *kind == Import
+        || *kind == Export
+        || *kind == Match
         || *kind == If
         || *kind == Then
         || *kind == Else
         || *kind == For
         || *kind == In
+        || *kind == While
         || *kind == Loop
         || *kind == Return
+        || *kind == Yield
+        || *kind == Break
         || *kind == Defer;
 }
 
-fn tokens_with_values() -> [(&'static str, TokenKind); 7] {
+fn tokens_with_values() -> [(&'static str, TokenKind); 9] {
     [
-        ("498035872", TokenKind::Integer(498035872)),
+        ("498035872", TokenKind::Integer(498035872, None)),
         ("some_identifier", TokenKind::Ident("some_identifier".into())),
-        ("0xdeadbeef", TokenKind::Integer(0xdeadbeef)),
-        ("1.234", TokenKind::Float(1.234)),
-        ("1e9", TokenKind::Float(1e9)),
-        ("0b101010", TokenKind::Integer(42)),
+        ("0xdeadbeef", TokenKind::Integer(0xdeadbeef, None)),
+        ("1.234", TokenKind::Float(1.234, None)),
+        ("1e9", TokenKind::Float(1e9, None)),
+        ("0b101010", TokenKind::Integer(42, None)),
+        ("'a'", TokenKind::Char('a')),
+        (r"'\u{1F600}'", TokenKind::Char('😀')),
         ("my_1st_variable", TokenKind::Ident("my_1st_variable".into())),
     ]
 }
 
-fn basic_tokens() -> [(&'static str, TokenKind); 60] {
+fn basic_tokens() -> [(&'static str, TokenKind); 68] {
     [
         ("(", TokenKind::LeftParen),
         (")", TokenKind::RightParen),
@@ -172,6 +670,7 @@ fn basic_tokens() -> [(&'static str, TokenKind); 60] {
         ("@", TokenKind::At),
         ("$", TokenKind::Dollar),
         (":", TokenKind::Colon),
+        (";", TokenKind::Semicolon),
         (".", TokenKind::Dot),
         //
         ("+", TokenKind::Plus),
@@ -201,6 +700,7 @@ fn basic_tokens() -> [(&'static str, TokenKind); 60] {
         //
         ("..", TokenKind::DotDot),
         ("->", TokenKind::RightArrow),
+        ("=>", TokenKind::FatArrow),
         ("|>", TokenKind::BarGt),
         ("::", TokenKind::ColonColon),
         //
@@ -216,14 +716,20 @@ fn basic_tokens() -> [(&'static str, TokenKind); 60] {
         ("fn", TokenKind::Function),
         ("type", TokenKind::Type),
         ("struct", TokenKind::Struct),
+        ("enum", TokenKind::Enum),
         ("import", TokenKind::Import),
+        ("export", TokenKind::Export),
+        ("match", TokenKind::Match),
         ("if", TokenKind::If),
         ("then", TokenKind::Then),
         ("else", TokenKind::Else),
         ("for", TokenKind::For),
         ("in", TokenKind::In),
+        ("while", TokenKind::While),
         ("loop", TokenKind::Loop),
         ("return", TokenKind::Return),
+        ("yield", TokenKind::Yield),
+        ("break", TokenKind::Break),
         ("defer", TokenKind::Defer),
     ]
 }
@@ -1,4 +1,7 @@
-use crate::cst::{Ident, Parser, Path, Type, TypeInner};
+use crate::cst::{
+    AssignmentOperator, Expr, ExprInner, Ident, InterpolatedPart, Item, Literal, Parser, Path,
+    Pattern, PatternInner, StmtInner, Type, TypeInner,
+};
 use crate::text::TextSpan;
 
 #[test]
@@ -142,6 +145,19 @@ fn parse_type() {
                 TS,
             ),
         ),
+        (
+            "fn a",
+            Type::new(
+                TypeInner::Function {
+                    args: vec![Type::new(
+                        TypeInner::Path(Path::new(vec![Ident::new("a".into(), TS)])),
+                        TS,
+                    )],
+                    returns: box Type::new(TypeInner::Tuple(vec![]), TS),
+                },
+                TS,
+            ),
+        ),
         (
             "fn a -> b",
             Type::new(
@@ -225,19 +241,728 @@ fn parse_type() {
                 TS,
             ),
         ),
+        (
+            "enum Kind {
+                EOF,
+                Literal: enum {
+                    Integer(u64),
+                    Pair: struct { first: a },
+                },
+            }",
+            Type::new(
+                TypeInner::Enum {
+                    ident: Some(Ident::new("Kind".into(), TS)),
+                    variants: vec![
+                        (Ident::new("EOF".into(), TS), None),
+                        (
+                            Ident::new("Literal".into(), TS),
+                            Some(Type::new(
+                                TypeInner::Enum {
+                                    ident: None,
+                                    variants: vec![
+                                        (
+                                            Ident::new("Integer".into(), TS),
+                                            Some(Type::new(
+                                                TypeInner::Tuple(vec![Type::new(
+                                                    TypeInner::Path(Path::new(vec![Ident::new(
+                                                        "u64".into(),
+                                                        TS,
+                                                    )])),
+                                                    TS,
+                                                )]),
+                                                TS,
+                                            )),
+                                        ),
+                                        (
+                                            Ident::new("Pair".into(), TS),
+                                            Some(Type::new(
+                                                TypeInner::Struct(vec![(
+                                                    Ident::new("first".into(), TS),
+                                                    Type::new(
+                                                        TypeInner::Path(Path::new(vec![
+                                                            Ident::new("a".into(), TS),
+                                                        ])),
+                                                        TS,
+                                                    ),
+                                                )]),
+                                                TS,
+                                            )),
+                                        ),
+                                    ],
+                                },
+                                TS,
+                            )),
+                        ),
+                    ],
+                },
+                TS,
+            ),
+        ),
     ];
 
     for (input, ty) in types.into_iter() {
         let mut parser = Parser::new(input.into());
-        let parsed =
-            parser
-                .parse_type()
-                .expect(&format!("Input: {}\n{:#?}", input, parser.diagnostics()));
-        assert!(parser.diagnostics().is_empty());
+        let parsed = parser.parse_type();
+        assert!(
+            parser.diagnostics().is_empty(),
+            "Input: {}\n{:#?}",
+            input,
+            parser.diagnostics()
+        );
         assert_types_eq(parsed, ty);
     }
 }
 
+#[test]
+fn parse_stmt() {
+    use AssignmentOperator as Op;
+    use StmtInner::*;
+    type Check = fn(&StmtInner) -> bool;
+
+    let stmts: Vec<(&str, Check)> = vec![
+        ("let a = 1", |s| matches!(s, Let { ty: None, .. })),
+        ("let a: u64 = 1", |s| matches!(s, Let { ty: Some(_), .. })),
+        ("let a: u64 = 1:2", |s| match s {
+            Let { value, .. } => matches!(value.inner, ExprInner::Range { .. }),
+            _ => false,
+        }),
+        ("for i in 1 {}", |s| matches!(s, For { .. })),
+        ("for i in 0:=n {}", |s| match s {
+            For { iter, .. } => {
+                matches!(iter.inner, ExprInner::Range { inclusive, .. } if inclusive)
+            }
+            _ => false,
+        }),
+        ("while true {}", |s| matches!(s, While { .. })),
+        ("return", |s| matches!(s, Return(None))),
+        ("return 1", |s| matches!(s, Return(Some(_)))),
+        ("yield 1", |s| matches!(s, Yield(_))),
+        ("break", |s| matches!(s, Break(None))),
+        ("break 1", |s| matches!(s, Break(Some(_)))),
+        // lvalues are not checked by the parser
+        ("1 = 2", |s| matches!(s, Assign { op: Op::Assign, .. })),
+        ("1 += 2", |s| matches!(s, Assign { op: Op::Add, .. })),
+        ("1 **= 2", |s| matches!(s, Assign { op: Op::Pow, .. })),
+        ("1 &= 2", |s| matches!(s, Assign { op: Op::BitAnd, .. })),
+        ("1 |= 2", |s| matches!(s, Assign { op: Op::BitOr, .. })),
+        ("1 ^= 2", |s| matches!(s, Assign { op: Op::BitXor, .. })),
+        ("{\n\tlet a = 1\n\tbreak\n\t2\n}", |s| match s {
+            Expr(block) => matches!(&block.inner, ExprInner::Block(b) if b.len() == 3),
+            _ => false,
+        }),
+        ("{\n\tlet a = 1\n\tbreak\n\ta\n}", |s| match s {
+            Expr(block) => matches!(&block.inner, ExprInner::Block(b) if b.len() == 3),
+            _ => false,
+        }),
+        ("for _ in range(0, n) {\n\ta = b\n\tb = c\n}", |s| match s {
+            For { body, .. } => matches!(&body.inner, Expr(_)),
+            _ => false,
+        }),
+        // A parenthesis on a new line is not a call
+        ("{\n\tf\n\t(a)\n}", |s| match s {
+            Expr(block) => matches!(&block.inner, ExprInner::Block(b) if b.len() == 2),
+            _ => false,
+        }),
+    ];
+
+    for (input, check) in stmts.into_iter() {
+        let mut parser = Parser::new(input.into());
+        let parsed = parser.parse_stmt();
+        assert!(
+            parser.diagnostics().is_empty(),
+            "Input: {}\n{:#?}",
+            input,
+            parser.diagnostics()
+        );
+        assert!(check(&parsed), "Input: {}\n{:#?}", input, parsed);
+        assert_eq!(*parsed.span(), TextSpan::new(0, input.len()));
+    }
+}
+
+#[test]
+fn parse_expr() {
+    let exprs = vec![
+        ("1 + 2 * 3", "(Add 1 (Mul 2 3))"),
+        ("1 * 2 + 3", "(Add (Mul 1 2) 3)"),
+        ("1 - 2 - 3", "(Sub (Sub 1 2) 3)"),
+        ("2 ** 3 ** 4", "(Pow 2 (Pow 3 4))"),
+        ("-2 ** 2", "(Neg (Pow 2 2))"),
+        ("!true == false", "(Eq (Not true) false)"),
+        ("-(1 + 2) * 3", "(Mul (Neg (Add 1 2)) 3)"),
+        ("$1 * 2", "(Mul (Deref 1) 2)"),
+        ("$a ** 2", "(Pow (Deref a) 2)"),
+        ("!a ** b", "(Pow (Not a) b)"),
+        ("&a |> f", "(Pipe (Ref a) f)"),
+        ("-a ** 2", "(Neg (Pow a 2))"),
+        (
+            "1 < 2 and 3 >= 4 or true",
+            "(Or (And (Lt 1 2) (Ge 3 4)) true)",
+        ),
+        ("1 < 2 == true", "(Eq (Lt 1 2) true)"),
+        ("(1 < 2) < 3", "(Lt (Lt 1 2) 3)"),
+        ("1 & 2 | 3 ^ 4", "(BitOr (BitAnd 1 2) (BitXor 3 4))"),
+        ("1 |> 2 |> 3", "(Pipe (Pipe 1 2) 3)"),
+        ("1 + 2 |> 3", "(Add 1 (Pipe 2 3))"),
+        ("a::b", "a::b"),
+        ("a :: b::c", "a::b::c"),
+        ("f()", "(call f)"),
+        ("f(1, a)(b)", "(call (call f 1 a) b)"),
+        ("xs[i + 1]", "(index xs (Add i 1))"),
+        ("t.a.b", "(. (. t a) b)"),
+        ("-a.f(b)[0]", "(Neg (index (call (. a f) b) 0))"),
+        ("xs[1:2]", "(index xs (range 1 2))"),
+        ("xs[1:]", "(index xs (range 1 _))"),
+        ("xs[:n - 1]", "(index xs (range _ (Sub n 1)))"),
+        ("xs[:]", "(index xs (range _ _))"),
+        (
+            "fib_t_rec(n) |> to_string()",
+            "(Pipe (call fib_t_rec n) (call to_string))",
+        ),
+        ("[]", "[]"),
+        ("[0, 1, 2]", "[0 1 2]"),
+        ("[1; 3]", "[1; 3]"),
+        ("[a; n + 1]", "[a; (Add n 1)]"),
+        ("[1, 4, 9, ..]", "[1 4 9 ..]"),
+        ("[1, 2, 3][:]", "(index [1 2 3] (range _ _))"),
+        ("to_array(1:4)", "(call to_array (range 1 4))"),
+        ("0:=n", "(range= 0 n)"),
+        ("xs[1:=i + 1]", "(index xs (range= 1 (Add i 1)))"),
+        ("a + 1:b |> f", "(range (Add a 1) (Pipe b f))"),
+        (":-1", "(range _ (Neg 1))"),
+        ("(1:)", "(range 1 _)"),
+        (
+            "Thing @{ a: 1:2, b: :, c: 3: }",
+            "(struct Thing (a (range 1 2)) (b (range _ _)) (c (range 3 _)))",
+        ),
+        (
+            "match a {\n\t0 => 1:\n\t:{ b } => b\n}",
+            "(match a (0 (range 1 _)) (:{b} b))",
+        ),
+        ("@[]", "@[]"),
+        ("@[1, fib(a)]", "@[1 (call fib a)]"),
+        ("@(1, 2)", "(tuple 1 2)"),
+        ("Thing @{ a: 1, b: 2, }", "(struct Thing (a 1) (b 2))"),
+        ("a::B @{ c: d @{} }", "(struct a::B (c (struct d)))"),
+        ("fn = 1", "(fn () 1)"),
+        ("fn () = 1", "(fn () 1)"),
+        ("fn n = n % 2 == 0", "(fn (n) (Eq (Mod n 2) 0))"),
+        ("fn n: u64 = n", "(fn (n:) n)"),
+        ("fn (a, b: u64) = a + b", "(fn (a b:) (Add a b))"),
+        (
+            "xs |> map(fn s = string::parse_int(s) ** 2)",
+            "(Pipe xs (call map (fn (s) (Pow (call string::parse_int s) 2))))",
+        ),
+        ("match a { _ => 1 }", "(match a (_ 1))"),
+        ("match a { 1 => b, c => d }", "(match a (1 b) (c d))"),
+        (
+            "match a { -1 => b, -2.5 => c, Some -3 => d }",
+            "(match a (-1 b) (-2.5 c) ((Some -3) d))",
+        ),
+        (
+            "match a {\n\t(1, _) => b\n\tc => d\n}",
+            "(match a ((tuple 1 _) b) (c d))",
+        ),
+        (
+            "match t.kind {
+                Token::Kind::Literal literal => match literal {
+                    Token::Kind::Literal::String s =>
+                        print(s)
+                    Token::Kind::Literal::Pair :{ first, second: (a, b) } =>
+                        ()
+                }
+                Token::Kind::EOF => eof
+            }",
+            "(match (. t kind) \
+             ((Token::Kind::Literal literal) (match literal \
+             ((Token::Kind::Literal::String s) (call print s)) \
+             ((Token::Kind::Literal::Pair :{first second:(tuple a b)}) (tuple )))) \
+             (Token::Kind::EOF eof))",
+        ),
+    ];
+
+    for (input, expected) in exprs.into_iter() {
+        let mut parser = Parser::new(input.into());
+        let parsed = parser.parse_expr();
+        assert!(
+            parser.diagnostics().is_empty(),
+            "Input: {}\n{:#?}",
+            input,
+            parser.diagnostics()
+        );
+        assert_eq!(sexpr(&parsed), expected, "Input: {}", input);
+        assert_eq!(*parsed.span(), TextSpan::new(0, input.len()));
+    }
+}
+
+#[test]
+fn chained_comparison() {
+    for input in &["1 < 2 < 3", "1 == 2 != 3", "1 < 2 >= 3 and true"] {
+        let mut parser = Parser::new((*input).into());
+        let expr = parser.parse_expr();
+        assert!(!matches!(expr.inner, ExprInner::Error));
+        assert_eq!(parser.diagnostics().len(), 1, "Input: {}", input);
+    }
+}
+
+#[test]
+fn char_literals() {
+    let mut parser = Parser::new("match c { 'a' => '\\n', _ => c }".into());
+    let expr = parser.parse_expr();
+    assert!(parser.diagnostics().is_empty());
+    assert_eq!(
+        sexpr(&expr),
+        "(match c (Literal(Char('a')) Literal(Char('\\n'))) (_ c))"
+    );
+}
+
+#[test]
+fn string_interpolation() {
+    #[rustfmt::skip]
+    let cases = [
+        (r#""%(sum)""#,                     "(str sum)"),
+        (r#""a %(x + 1) b""#,               r#"(str "a " (Add x 1) " b")"#),
+        (r#""%(f(")"))%((1))""#,            "(str (call f Literal(String(\")\"))) 1)"),
+        (r#""%(g("%(a) %(b)"))!""#,         r#"(str (call g (str a " " b)) "!")"#),
+        (r#""100\% %(n)""#,                 r#"(str "100% " n)"#),
+    ];
+
+    for (input, expected) in cases.iter() {
+        let mut parser = Parser::new((*input).into());
+        let expr = parser.parse_expr();
+        assert!(
+            parser.diagnostics().is_empty(),
+            "Input: {}\n{:#?}",
+            input,
+            parser.diagnostics()
+        );
+        assert_eq!(sexpr(&expr), *expected, "Input: {}", input);
+    }
+
+    // The embedded expressions have spans in the file, not in the string
+    let mut parser = Parser::new(r#"let s = "[%(a + b)]""#.into());
+    let global = parser.parse_module().globals()[0].1.value().clone();
+    match &global.inner {
+        ExprInner::Interpolated(parts) => match &parts[1] {
+            InterpolatedPart::Expr(expr) => assert_eq!(*expr.span(), TextSpan::new(12, 17)),
+            part => panic!("Expected an expression, got {:?}", part),
+        },
+        inner => panic!("Expected an interpolated string, got {:?}", inner),
+    }
+
+    let mut parser = Parser::new(r#""%(a b)""#.into());
+    parser.parse_expr();
+    let diagnostic = &parser.diagnostics()[0];
+    assert_eq!(diagnostic.message(), "Unexpected 'b'");
+    assert_eq!(*diagnostic.span(), TextSpan::new(5, 6));
+    assert_eq!(diagnostic.labels()[0].message(), "expected ')'");
+
+    for input in [r#""%()""#, r#""%( )""#].iter() {
+        let mut parser = Parser::new((*input).into());
+        parser.parse_expr();
+        assert_eq!(parser.diagnostics().len(), 1, "Input: {}", input);
+        let diagnostic = &parser.diagnostics()[0];
+        assert_eq!(diagnostic.code(), "E0020");
+        assert_eq!(*diagnostic.span(), TextSpan::new(1, input.len() - 1));
+        assert_eq!(diagnostic.labels()[0].message(), "expected expression");
+    }
+}
+
+#[test]
+fn lexer_diagnostics() {
+    // The lexer's diagnostics come along with the parser's, in the order of where they are
+    let mut parser = Parser::new("f(\"\\q\", 0b12) + 256u8".into());
+    parser.parse_expr();
+    let codes: Vec<_> = parser.diagnostics().iter().map(|d| d.code()).collect();
+    assert_eq!(codes, ["E0002", "E0016", "E0019"]);
+
+    let mut parser = Parser::new("[u8; 1e]".into());
+    parser.parse_type();
+    let codes: Vec<_> = parser.diagnostics().iter().map(|d| d.code()).collect();
+    assert_eq!(codes, ["E0017"]);
+}
+
+#[test]
+fn parse_module() {
+    let files = [
+        include_str!("../../tests/block.hel"),
+        include_str!("../../tests/function.hel"),
+        include_str!("../../tests/hello.hel"),
+        include_str!("../../tests/path.hel"),
+        include_str!("../../tests/struct.hel"),
+        include_str!("../../tests/package/lib.hel"),
+        include_str!("../../tests/package/main.hel"),
+    ];
+
+    for file in files.iter() {
+        let mut parser = Parser::new((*file).into());
+        parser.parse_module();
+        assert!(
+            parser.diagnostics().is_empty(),
+            "Input: {}\n{:#?}",
+            file,
+            parser.diagnostics()
+        );
+    }
+
+    let mut parser = Parser::new("import a::b\nexport fn f() = 1\nexport let a = 2".into());
+    let module = parser.parse_module();
+    assert!(parser.diagnostics().is_empty());
+    assert!(matches!(
+        &module.items()[..],
+        [(false, Item::Import(_)), (true, Item::Function(_))]
+    ));
+    assert!(matches!(&module.globals()[..], [(true, _)]));
+}
+
+#[test]
+fn colon_curly() {
+    let file = include_str!("../../tests/inlineenumsandstructsandstuff.hel");
+    let mut parser = Parser::new(file.into());
+    let module = parser.parse_module();
+    assert!(
+        parser.diagnostics().is_empty(),
+        "{:#?}",
+        parser.diagnostics()
+    );
+
+    let main = match &module.items()[..] {
+        [(false, Item::TypeDecls(_)), (false, Item::Function(main))] => main,
+        items => panic!("Expected a type and a function, got {:#?}", items),
+    };
+    let stmts = match &main.body().inner {
+        ExprInner::Block(stmts) => stmts,
+        body => panic!("Expected a block, got {:#?}", body),
+    };
+    assert_eq!(stmts.len(), 5);
+
+    match &stmts[0].inner {
+        StmtInner::Let { value, .. } => assert_eq!(
+            sexpr(value),
+            "(struct Token (line 1) (col 1) (kind Token::Kind::LeftParen))"
+        ),
+        stmt => panic!("Expected a let, got {:#?}", stmt),
+    }
+    match &stmts[1].inner {
+        StmtInner::Expr(Expr {
+            inner:
+                ExprInner::If {
+                    condition,
+                    then,
+                    els: None,
+                },
+            ..
+        }) => {
+            assert_eq!(sexpr(condition), "(Eq (. t kind) Token::Kind::EOF)");
+            assert!(matches!(
+                &then.inner,
+                StmtInner::Expr(Expr { inner: ExprInner::Block(stmts), .. }) if stmts.is_empty()
+            ));
+        }
+        stmt => panic!("Expected an if, got {:#?}", stmt),
+    }
+    match &stmts[3].inner {
+        StmtInner::Assign { value, .. } => assert_eq!(
+            sexpr(value),
+            "(struct Token::Kind::Literal::Pair (first (Ref t)) (second (Ref t)))"
+        ),
+        stmt => panic!("Expected an assignment, got {:#?}", stmt),
+    }
+
+    let mut parser = Parser::new("for i in 1:4 :{ }".into());
+    match parser.parse_stmt().inner {
+        StmtInner::For { iter, .. } => assert_eq!(sexpr(&iter), "(range 1 4)"),
+        stmt => panic!("Expected a for, got {:#?}", stmt),
+    }
+    assert!(parser.diagnostics().is_empty());
+}
+
+#[test]
+fn parse_package() {
+    let mut parser = Parser::new(include_str!("../../tests/package/main.hel").into());
+    let package = parser.parse_package();
+    assert!(parser.diagnostics().is_empty());
+    assert!(!package.root().items().is_empty());
+}
+
+#[test]
+fn doc_comments() {
+    let input = "## The answer
+export let answer = 42
+## Adds
+## two numbers
+fn add(a: u64, b: u64) = a + b
+## A point
+struct Point { x: u64 }
+fn undocumented() = 1
+## Not attached to anything inside
+type Id = u64
+";
+    let mut parser = Parser::new(input.into());
+    let module = parser.parse_module();
+    assert!(parser.diagnostics().is_empty());
+
+    let docs: Vec<_> = module
+        .items()
+        .iter()
+        .map(|(_, item)| match item {
+            Item::Function(function) => function.doc().as_deref(),
+            Item::TypeDecls(decl) => decl.doc().as_deref(),
+            Item::Import(_) => unreachable!(),
+        })
+        .collect();
+    assert_eq!(
+        docs,
+        [
+            Some("Adds\ntwo numbers"),
+            Some("A point"),
+            None,
+            Some("Not attached to anything inside")
+        ]
+    );
+    assert_eq!(module.globals()[0].1.doc().as_deref(), Some("The answer"));
+}
+
+#[test]
+fn equal_before_block_body() {
+    let mut parser = Parser::new("fn f() = {}".into());
+    assert_eq!(parser.parse_module().items().len(), 1);
+    assert_eq!(parser.diagnostics().len(), 1);
+}
+
+#[test]
+fn function_item_in_expression() {
+    for input in &[
+        "fn f(a) = a",
+        "g(\n\tfn f() = 1\n)",
+        "{\n\tfn f(a: u64) = a\n}",
+    ] {
+        let mut parser = Parser::new((*input).into());
+        parser.parse_expr();
+        assert_eq!(parser.diagnostics().len(), 1, "Input: {}", input);
+        assert_eq!(parser.diagnostics()[0].code(), "E0008", "Input: {}", input);
+    }
+
+    // Closures may start on a new line, only `fn name(` is an item
+    let mut parser = Parser::new("let f =\n\tfn (a, b) = a\nfn g(a: u64) = a".into());
+    let module = parser.parse_module();
+    assert!(
+        parser.diagnostics().is_empty(),
+        "{:#?}",
+        parser.diagnostics()
+    );
+    assert_eq!(module.items().len(), 1);
+    assert_eq!(sexpr(module.globals()[0].1.value()), "(fn (a b) a)");
+}
+
+#[test]
+fn unexpected_token_messages() {
+    #[rustfmt::skip]
+    let cases = [
+        ("fn f(a: int b) = a",     "Unexpected 'b'",              "expected ')' or ','"),
+        ("fn f() = (1",            "Unexpected end of file",      "expected ')'"),
+        ("let a = @b",             "Unexpected 'b'",              "expected '(' or '['"),
+        ("import \"a long string, not a path\"",
+                                   "Unexpected string literal",   "expected identifier"),
+    ];
+
+    for (input, message, label) in cases.iter() {
+        let mut parser = Parser::new((*input).into());
+        parser.parse_module();
+        let diagnostic = &parser.diagnostics()[0];
+        assert_eq!(diagnostic.message(), *message, "Input: {}", input);
+        assert_eq!(diagnostic.labels()[0].message(), label, "Input: {}", input);
+    }
+}
+
+#[test]
+fn recover_expr() {
+    #[rustfmt::skip]
+    let exprs = [
+        ("f(1 +, 2)",                     "(call f error 2)"),
+        ("f(1 2, 3)",                     "(call f 1 3)"),
+        ("[1, ), 2]",                     "[1 error 2]"),
+        ("@(1 * (2 3), 4)",               "(tuple (Mul 1 2) 4)"),
+        ("match a { ) => 1 \n 2 => 3 }", "(match a (2 3))"),
+        ("g(f(1, 2)",                     "error"),
+        ("f(xs[1 +], 2)",                 "(call f (index xs error) 2)"),
+        ("[1 +]",                         "[error]"),
+        ("f(1 +]",                        "(call f error)"),
+        ("xs[1 +) + 1",                   "error"),
+    ];
+
+    for (input, expected) in exprs.iter() {
+        let mut parser = Parser::new((*input).into());
+        let parsed = parser.parse_expr();
+        assert_eq!(sexpr(&parsed), *expected, "Input: {}", input);
+        assert_eq!(
+            parser.diagnostics().len(),
+            1,
+            "Input: {}\n{:#?}",
+            input,
+            parser.diagnostics()
+        );
+    }
+
+    // The `let` isn't also reported for not being a separator
+    let mut parser = Parser::new("g(1,\n  let y = 2".into());
+    parser.parse_expr();
+    let messages = parser
+        .diagnostics()
+        .iter()
+        .map(|d| d.message())
+        .collect::<Vec<&str>>();
+    assert_eq!(messages, ["Unexpected 'let'", "Unexpected end of file"]);
+}
+
+#[test]
+fn recover_module() {
+    let input = "fn a() = 1 +
+fn b(x: ) = x
+let c = )
+fn d() = [1, 2
+import
+fn e() = 3
+";
+    let mut parser = Parser::new(input.into());
+    let module = parser.parse_module();
+    assert_eq!(parser.diagnostics().len(), 5, "{:#?}", parser.diagnostics());
+
+    let bodies = module
+        .items()
+        .iter()
+        .map(|(_, item)| match item {
+            Item::Function(function) => sexpr(function.body()),
+            _ => panic!("Expected only functions, got {:#?}", item),
+        })
+        .collect::<Vec<String>>();
+    assert_eq!(bodies, ["error", "x", "error", "3"]);
+    assert!(matches!(
+        &module.globals()[..],
+        [(false, global)] if matches!(global.value().inner, ExprInner::Error)
+    ));
+
+    let mut parser = Parser::new("import\nlet a = 1\nfn f() = 2".into());
+    let module = parser.parse_module();
+    assert_eq!(parser.diagnostics().len(), 1, "{:#?}", parser.diagnostics());
+    assert!(matches!(&module.items()[..], [(false, Item::Function(_))]));
+    assert!(matches!(
+        &module.globals()[..],
+        [(false, global)] if sexpr(global.value()) == "1"
+    ));
+}
+
+/// Formats an expression as an s-expression, ignoring spans
+fn sexpr(expr: &Expr) -> String {
+    match &expr.inner {
+        ExprInner::Literal(Literal::Integer(i, _)) => i.to_string(),
+        ExprInner::Literal(Literal::Bool(b)) => b.to_string(),
+        ExprInner::Binary {
+            left,
+            operator,
+            right,
+        } => format!("({:?} {} {})", operator, sexpr(left), sexpr(right)),
+        ExprInner::Unary { operator, operand } => format!("({:?} {})", operator, sexpr(operand)),
+        ExprInner::Path(path) => sexpr_path(path),
+        ExprInner::Evoc { func, args } if args.is_empty() => format!("(call {})", sexpr(func)),
+        ExprInner::Evoc { func, args } => format!("(call {} {})", sexpr(func), sexprs(args)),
+        ExprInner::Indexing { into, index } => format!("(index {} {})", sexpr(into), sexpr(index)),
+        ExprInner::FieldAccess { on, field } => format!("(. {} {})", sexpr(on), field.as_str()),
+        ExprInner::Tuple(values) => format!("(tuple {})", sexprs(values)),
+        ExprInner::SizedArray(values, None) => format!("[{}]", sexprs(values)),
+        ExprInner::SizedArray(values, Some(size)) => {
+            format!("[{}; {}]", sexprs(values), sexpr(size))
+        }
+        ExprInner::DynamicArray(values) => format!("[{} ..]", sexprs(values)),
+        ExprInner::List(values) => format!("@[{}]", sexprs(values)),
+        ExprInner::Struct { path, values } => {
+            let mut s = format!("(struct {}", sexpr_path(path));
+            for (field, value) in values {
+                s.push_str(&format!(" ({} {})", field.as_str(), sexpr(value)));
+            }
+            s + ")"
+        }
+        ExprInner::Closure { args, body } => {
+            let args = args
+                .iter()
+                .map(|(arg, ty)| format!("{}{}", arg.as_str(), if ty.is_some() { ":" } else { "" }))
+                .collect::<Vec<String>>();
+            format!("(fn ({}) {})", args.join(" "), sexpr(body))
+        }
+        ExprInner::Match { on, arms } => {
+            let mut s = format!("(match {}", sexpr(on));
+            for (pattern, value) in arms {
+                s.push_str(&format!(" ({} {})", spattern(pattern), sexpr(value)));
+            }
+            s + ")"
+        }
+        ExprInner::Range {
+            start,
+            end,
+            inclusive,
+        } => {
+            let endpoint = |e: &Option<Box<Expr>>| e.as_deref().map(sexpr).unwrap_or("_".into());
+            let range = if *inclusive { "range=" } else { "range" };
+            format!("({} {} {})", range, endpoint(start), endpoint(end))
+        }
+        ExprInner::Interpolated(parts) => {
+            let parts = parts
+                .iter()
+                .map(|part| match part {
+                    InterpolatedPart::Literal(s) => format!("{:?}", s),
+                    InterpolatedPart::Expr(expr) => sexpr(expr),
+                })
+                .collect::<Vec<String>>();
+            format!("(str {})", parts.join(" "))
+        }
+        ExprInner::Error => "error".into(),
+        inner => format!("{:?}", inner),
+    }
+}
+
+fn spattern(pattern: &Pattern) -> String {
+    match &pattern.inner {
+        PatternInner::Wildcard => "_".into(),
+        PatternInner::Binding(ident) => ident.to_string(),
+        PatternInner::Literal(Literal::Integer(i, _)) => i.to_string(),
+        PatternInner::Negative(Literal::Integer(i, _)) => format!("-{}", i),
+        PatternInner::Negative(Literal::Float(f, _)) => format!("-{}", f),
+        PatternInner::Tuple(patterns) => {
+            let patterns = patterns.iter().map(spattern).collect::<Vec<String>>();
+            format!("(tuple {})", patterns.join(" "))
+        }
+        PatternInner::Variant {
+            path,
+            payload: None,
+        } => sexpr_path(path),
+        PatternInner::Variant {
+            path,
+            payload: Some(payload),
+        } => format!("({} {})", sexpr_path(path), spattern(payload)),
+        PatternInner::Struct(fields) => {
+            let fields = fields
+                .iter()
+                .map(|(field, pattern)| match pattern {
+                    Some(pattern) => format!("{}:{}", field.as_str(), spattern(pattern)),
+                    None => field.to_string(),
+                })
+                .collect::<Vec<String>>();
+            format!(":{{{}}}", fields.join(" "))
+        }
+        inner => format!("{:?}", inner),
+    }
+}
+
+fn sexprs(exprs: &[Expr]) -> String {
+    exprs.iter().map(sexpr).collect::<Vec<String>>().join(" ")
+}
+
+fn sexpr_path(path: &Path) -> String {
+    path.iter()
+        .map(|ident| ident.as_str())
+        .collect::<Vec<&str>>()
+        .join("::")
+}
+
 fn assert_types_eq(t1: Type, t2: Type) {
     let error_message = format!("\ngot\n{:?}\nexpected\n{:?}\n", &t1, t2);
     let fail = || panic!(error_message);
@@ -334,5 +1059,32 @@ fn assert_types_eq(t1: Type, t2: Type) {
                 fail();
             }
         }
+        TypeInner::Enum {
+            ident: n1,
+            variants: v1s,
+        } => {
+            if let TypeInner::Enum {
+                ident: n2,
+                variants: v2s,
+            } = t2.inner
+            {
+                assert_eq!(n1, n2);
+                assert_eq!(v1s.len(), v2s.len());
+                for ((n1, p1), (n2, p2)) in v1s.into_iter().zip(v2s) {
+                    assert_eq!(n1, n2);
+                    assert_eq!(p1.is_some(), p2.is_some());
+                    if let (Some(p1), Some(p2)) = (p1, p2) {
+                        assert_types_eq(p1, p2);
+                    }
+                }
+            } else {
+                fail();
+            }
+        }
+        TypeInner::Error => {
+            if !matches!(t2.inner, TypeInner::Error) {
+                fail();
+            }
+        }
     }
 }
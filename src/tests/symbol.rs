@@ -0,0 +1,18 @@
+use std::thread;
+
+use crate::symbol::Symbol;
+
+#[test]
+fn interning() {
+    let a = Symbol::intern("interned");
+    assert_eq!(a, Symbol::intern(&String::from("interned")));
+    assert_ne!(a, Symbol::intern("Interned"));
+    assert_eq!(a.as_str(), "interned");
+    assert_eq!(a.to_string(), "interned");
+    assert_eq!(format!("{:?}", a), "\"interned\"");
+    assert_eq!(Symbol::intern("").as_str(), "");
+
+    // Symbols are the same on every thread
+    let b = thread::spawn(|| Symbol::intern("interned")).join().unwrap();
+    assert_eq!(a, b);
+}
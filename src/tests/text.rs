@@ -1,4 +1,5 @@
-use crate::text::SourceCode;
+use crate::lex::Lexer;
+use crate::text::{SourceCode, SourceMap, TextSpan};
 
 #[test]
 fn line_col() {
@@ -17,3 +18,41 @@ fn line_col() {
         assert_eq!(threes.next(), Some((4, 8)));
     }
 }
+
+#[test]
+fn multi_byte_columns() {
+    let source = "# varför\nlåt 𝄞 = ä\n";
+    let source_code = SourceCode::new(source.into());
+    let position = |c| source.find(c).unwrap();
+
+    assert_eq!(source_code.line_col(position('ö')), (1, 7));
+    assert_eq!(source_code.line_col(position('=')), (2, 7));
+    assert_eq!(source_code.line_col_utf16(position('=')), (2, 8));
+    assert_eq!(source_code.line_col(position('ä')), (2, 9));
+    assert_eq!(source_code.line_col_utf16(position('ä')), (2, 10));
+    assert_eq!(source_code.line_col(source.len()), (3, 1));
+}
+
+#[test]
+fn source_map() {
+    let mut sources = SourceMap::default();
+    let main = sources.add("main.hel", "let a = 1\n".into());
+    let lib = sources.add("lib.hel", "fn f() = 2\nfn g() = 3\n".into());
+    let empty = sources.add("empty.hel", "".into());
+
+    assert_eq!(sources.file(main).start(), 0);
+    assert_eq!(sources.find(0), main);
+    // The EOF position belongs to the file that ends there
+    assert_eq!(sources.find(10), main);
+    assert_eq!(sources.find(11), lib);
+    assert_eq!(sources.find(sources.file(lib).end()), lib);
+    assert_eq!(sources.find(sources.file(empty).start()), empty);
+    assert_eq!(sources.locate(11 + 14), ("lib.hel", 2, 4));
+
+    let mut lexer = Lexer::from(sources.file(lib));
+    lexer.eat();
+    let f = lexer.eat();
+    assert_eq!(*f.span(), TextSpan::new(14, 15));
+    assert_eq!(lexer.source_text(*f.span()), "f");
+    assert_eq!(sources.file_of(*f.span()).name(), "lib.hel");
+}
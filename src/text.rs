@@ -1,5 +1,7 @@
 use std::iter;
 
+/// A range of UTF-8 byte offsets into the source code, the end being exclusive. Both ends are
+/// always on char boundaries.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TextSpan {
     start: usize,
@@ -106,7 +108,22 @@ impl SourceCode {
         }
     }
 
+    /// The line and column of the byte offset `index`, both starting at 1. Columns count chars.
     pub fn line_col(&self, index: usize) -> (usize, usize) {
+        let (line, start, index) = self.line_of(index);
+        (line + 1, self.text[start..index].chars().count() + 1)
+    }
+
+    /// Like `line_col`, but columns count UTF-16 code units, as editors speaking LSP expect
+    pub fn line_col_utf16(&self, index: usize) -> (usize, usize) {
+        let (line, start, index) = self.line_of(index);
+        (line + 1, self.text[start..index].encode_utf16().count() + 1)
+    }
+
+    /// The 0-based line containing `index`, where that line starts, and `index` clamped to the
+    /// end of the text
+    fn line_of(&self, index: usize) -> (usize, usize, usize) {
+        let index = usize::min(index, self.text.len());
         let line_starts = &self.line_starts;
 
         let mut low = 0;
@@ -123,6 +140,101 @@ impl SourceCode {
         }
         let line = if index < line_starts[high] { low } else { high };
 
-        (line + 1, index - line_starts[line] + 1)
+        (line, line_starts[line], index)
+    }
+}
+
+/// Identifies a file in a `SourceMap`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FileId(usize);
+
+/// A file loaded into a `SourceMap`
+#[derive(Debug)]
+pub struct SourceFile {
+    name: String,
+    code: SourceCode,
+    // The position of the first char of the file in the source map
+    start: usize,
+}
+
+impl SourceFile {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn code(&self) -> &SourceCode {
+        &self.code
+    }
+
+    pub fn text(&self) -> &str {
+        self.code.text()
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// The position just after the last char, where the file's EOF token is
+    pub fn end(&self) -> usize {
+        self.start + self.code.text().len()
+    }
+
+    /// Turns a position in the source map into one relative to the start of the file
+    pub fn local(&self, position: usize) -> usize {
+        position - self.start
+    }
+
+    pub fn line_col(&self, position: usize) -> (usize, usize) {
+        self.code.line_col(self.local(position))
+    }
+}
+
+/// Owns the source code of every loaded file.
+///
+/// Each file gets its own range of positions, starting after the end of the previous file, so
+/// a `TextSpan` on its own is enough to find which file it points into.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn add<N: Into<String>>(&mut self, name: N, text: String) -> FileId {
+        // The gap of one keeps the EOF position of a file from being the start of the next one
+        let start = self.files.last().map_or(0, |file| file.end() + 1);
+        self.files.push(SourceFile {
+            name: name.into(),
+            code: SourceCode::new(text),
+            start,
+        });
+        FileId(self.files.len() - 1)
+    }
+
+    pub fn file(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0]
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+        self.files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| (FileId(i), file))
+    }
+
+    /// The file containing `position`
+    pub fn find(&self, position: usize) -> FileId {
+        let after = self.files.partition_point(|file| file.start <= position);
+        FileId(after.saturating_sub(1))
+    }
+
+    pub fn file_of(&self, span: TextSpan) -> &SourceFile {
+        self.file(self.find(span.start()))
+    }
+
+    /// The name, line and column of `position`, lines and columns starting at 1
+    pub fn locate(&self, position: usize) -> (&str, usize, usize) {
+        let file = self.file(self.find(position));
+        let (line, col) = file.line_col(position);
+        (file.name(), line, col)
     }
 }
@@ -10,7 +10,8 @@ pub enum Type {
     Reference(Box<Type>),
     List(Box<Type>),
     Integer { size: u8, signed: bool },
-    // TODO: add Char? or maybe just use Integer { size: 4, signed: false } (u32)
+    Char,             // a unicode scalar value
+    Range(Box<Type>), // start:end  or  start:=end over integers or chars
 }
 
 #[derive(Debug, PartialEq)]
@@ -37,6 +38,17 @@ impl Type {
             Type::Reference(_) => POINTER_SIZE,
             Type::List(_) => POINTER_SIZE * 3, // ptr, size, capacity
             Type::Integer { size, .. } => *size,
+            Type::Char => 4,
+            Type::Range(element) => element.size() * 2 + 1, // start, end, inclusive
+        }
+    }
+
+    /// The type of the values a `for` loop gets when iterating over this type
+    pub fn element(&self) -> Option<&Type> {
+        match self {
+            Type::Range(element) | Type::List(element) => Some(element),
+            Type::Reference(box Type::List(element)) => Some(element),
+            _ => None,
         }
     }
 }